@@ -0,0 +1,138 @@
+//Сколько буферов, bind group и текстур создается в каждом кадре и растет ли число живых ресурсов
+//устройства. Созданные считает buffers::allocations, живые - сам wgpu, так что в них попадают и текстуры SMAA.
+//Рисуем во внеэкранную текстуру, чтобы бенчмарк работал без окна: cargo bench --bench frame_allocations
+use std::time::Instant;
+
+use render::config::{Antialiasing, RenderConfig, RingGeometry};
+use render::ui::bloom::Bloom;
+use render::ui::buffers;
+use render::ui::colors::DEFAULT_THEME;
+use render::ui::renderer::{Renderer, Ring, RingShape, Scene, SceneLayer};
+use render::ui::setup;
+use render::ui::vertex_generator::SEGMENTS;
+
+const WIDTH: u32 = 400;
+const HEIGHT: u32 = 1080;
+const FRAMES: usize = 120;
+
+// Живые ресурсы устройства: буферы, bind group, текстуры, их view, командные буферы
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Resources([usize; 5]);
+
+impl Resources {
+    fn count(instance: &wgpu::Instance, backend: wgpu::Backend) -> Resources {
+        let report = instance.generate_report().expect("wgpu-core backend");
+        let hub = report.hub_report(backend);
+        Resources([
+            hub.buffers.num_kept_from_user,
+            hub.bind_groups.num_kept_from_user,
+            hub.textures.num_kept_from_user,
+            hub.texture_views.num_kept_from_user,
+            hub.command_buffers.num_kept_from_user,
+        ])
+    }
+
+    fn since(self, before: Resources) -> [isize; 5] {
+        std::array::from_fn(|i| self.0[i] as isize - before.0[i] as isize)
+    }
+}
+
+fn main() {
+    let instance = wgpu::Instance::default();
+    let (adapter, device, queue) = match pollster::block_on(setup::headless_on(&instance, false)) {
+        Ok(hardware) => hardware,
+        Err(err) => {
+            eprintln!("{err}, skipping");
+            return;
+        }
+    };
+    let backend = adapter.get_info().backend;
+
+    let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d { width: WIDTH, height: HEIGHT, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let configs = [
+        ("smaa", RenderConfig::default()),
+        ("msaa4 + sdf + bloom", RenderConfig { antialiasing: Antialiasing::Msaa4, geometry: RingGeometry::Sdf, bloom: true }),
+    ];
+    let mut allocated = false;
+    let mut grew = false;
+    for (name, render) in configs {
+        let before_setup = Resources::count(&instance, backend);
+        let created_setup = buffers::allocations();
+        let mut renderer = Renderer::new(&device, &queue, format, WIDTH, HEIGHT, RingShape::default(), render);
+        device.poll(wgpu::Maintain::Wait);
+        let before_frames = Resources::count(&instance, backend);
+        let created_before = buffers::allocations();
+        println!(
+            "{name}: setup {} allocations, live {:?} (buffers, bind groups, textures, views, command buffers)",
+            created_before - created_setup,
+            before_frames.since(before_setup),
+        );
+
+        //Кадры с меняющимся масштабом, вращением и спектром - худший случай для записи в буферы
+        let started = Instant::now();
+        for frame in 0..FRAMES {
+            renderer.render(&device, &queue, &view, &scene(frame));
+        }
+        device.poll(wgpu::Maintain::Wait);
+        let elapsed = started.elapsed();
+        let created = buffers::allocations() - created_before;
+        let growth = Resources::count(&instance, backend).since(before_frames);
+        allocated |= created != 0;
+        grew |= growth.iter().any(|&count| count != 0);
+
+        println!(
+            "{name}: {FRAMES} frames, {:.2} allocations per frame, growth {growth:?}, {:.3} ms per frame",
+            created as f64 / FRAMES as f64,
+            elapsed.as_secs_f64() * 1000.0 / FRAMES as f64,
+        );
+    }
+    if allocated {
+        eprintln!("buffers, bind groups or textures are created every frame");
+    }
+    if grew {
+        eprintln!("resources leak from frame to frame");
+    }
+    if allocated || grew {
+        std::process::exit(1);
+    }
+}
+
+fn scene(frame: usize) -> Scene {
+    let t = frame as f32 / 60.0;
+    let offsets: Vec<f32> = (0..SEGMENTS).map(|i| ((i as f32 * 0.1 + t * 5.0).sin() * 0.05).abs()).collect();
+    Scene {
+        theme: DEFAULT_THEME,
+        layers: vec![
+            SceneLayer {
+                ring: Ring::Dashed,
+                outer_offsets: Vec::new(),
+                inner_offsets: Vec::new(),
+                scale: 0.8 + t.sin() * 0.1,
+                rotation: t * 1.8,
+                tint: [1.0, 1.0, 1.0, 1.0 - (t % 1.0)],
+            },
+            SceneLayer {
+                ring: Ring::Full,
+                outer_offsets: offsets.clone(),
+                inner_offsets: offsets,
+                scale: 0.8,
+                rotation: 0.0,
+                tint: [1.0, 1.0, 1.0, t % 1.0],
+            },
+        ],
+        overlay: Vec::new(),
+        bloom: Bloom::default(),
+    }
+}
@@ -0,0 +1,7 @@
+pub mod input;
+pub mod level;
+pub mod spectrum;
+pub mod waveform;
+mod wav;
+#[cfg(feature = "microphone")]
+mod microphone;
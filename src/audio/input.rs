@@ -0,0 +1,125 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use ringbuf::traits::{Consumer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
+
+use crate::audio::wav::WavPlayer;
+
+// Емкость кольцевого буфера между аудио-потоком и циклом отрисовки (~1 сек при 48 кГц)
+const RING_CAPACITY: usize = 48_000;
+// Сколько последних сэмплов держим для отрисовки
+pub const WINDOW_SIZE: usize = 2048;
+
+pub type SampleProducer = HeapProd<f32>;
+
+#[derive(Debug, Clone)]
+pub enum InputSource {
+    Microphone,
+    Wav(PathBuf),
+}
+
+impl InputSource {
+    //Микрофон есть только в сборке с feature "microphone", без нее запись выключена
+    pub fn is_available(&self) -> bool {
+        match self {
+            InputSource::Microphone => cfg!(feature = "microphone"),
+            InputSource::Wav(_) => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(not(feature = "microphone"), allow(dead_code))]
+pub enum AudioError {
+    NoDevice,
+    Unsupported(String),
+    Stream(String),
+    Wav(String),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::NoDevice => write!(f, "no audio input device available"),
+            AudioError::Unsupported(msg) => write!(f, "unsupported input: {msg}"),
+            AudioError::Stream(msg) => write!(f, "audio stream error: {msg}"),
+            AudioError::Wav(msg) => write!(f, "wav source error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+// Держит поток живым: при drop поток останавливается
+#[allow(dead_code)]
+enum Stream {
+    #[cfg(feature = "microphone")]
+    Microphone(cpal::Stream),
+    Wav(WavPlayer),
+}
+
+pub struct AudioInput {
+    _stream: Stream,
+    consumer: HeapCons<f32>,
+    window: Vec<f32>,
+    scratch: Vec<f32>,
+    sample_rate: u32,
+}
+
+impl AudioInput {
+    pub fn start(source: &InputSource) -> Result<AudioInput, AudioError> {
+        let (producer, consumer) = HeapRb::<f32>::new(RING_CAPACITY).split();
+
+        let (stream, sample_rate) = match source {
+            #[cfg(feature = "microphone")]
+            InputSource::Microphone => {
+                let (stream, sample_rate) = crate::audio::microphone::open(producer)?;
+                (Stream::Microphone(stream), sample_rate)
+            }
+            #[cfg(not(feature = "microphone"))]
+            InputSource::Microphone => {
+                drop(producer);
+                return Err(AudioError::Unsupported(
+                    "built without the `microphone` feature, use --wav <file>".to_string(),
+                ));
+            }
+            InputSource::Wav(path) => {
+                let player = WavPlayer::open(path, producer)?;
+                let sample_rate = player.sample_rate();
+                (Stream::Wav(player), sample_rate)
+            }
+        };
+
+        Ok(AudioInput {
+            _stream: stream,
+            consumer,
+            window: vec![0.0; WINDOW_SIZE],
+            scratch: vec![0.0; RING_CAPACITY],
+            sample_rate,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.window
+    }
+
+    //Забираем все накопившиеся сэмплы из кольцевого буфера, окно хранит последние WINDOW_SIZE
+    pub fn poll(&mut self) {
+        let count = self.consumer.pop_slice(&mut self.scratch);
+        if count == 0 {
+            return;
+        }
+        let fresh = &self.scratch[..count];
+        if count >= WINDOW_SIZE {
+            self.window.copy_from_slice(&fresh[count - WINDOW_SIZE..]);
+        } else {
+            self.window.drain(..count);
+            self.window.extend_from_slice(fresh);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// Огибающая громкости: быстро реагирует на рост сигнала (attack) и плавно затухает (release).
+// attack и release - постоянные времени в секундах
+pub struct LevelMeter {
+    attack: f32,
+    release: f32,
+    level: f32,
+}
+
+impl LevelMeter {
+    pub fn new(attack: f32, release: f32) -> LevelMeter {
+        LevelMeter {
+            attack,
+            release,
+            level: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.level = 0.0;
+    }
+
+    //Возвращает сглаженный уровень в диапазоне 0..1
+    pub fn update(&mut self, samples: &[f32], dt: f32) -> f32 {
+        self.follow(raw_level(samples), dt)
+    }
+
+    //Та же огибающая для уровня, пришедшего снаружи
+    pub fn follow(&mut self, target: f32, dt: f32) -> f32 {
+        let target = target.clamp(0.0, 1.0);
+        let time_constant = if target > self.level { self.attack } else { self.release };
+        self.level += (target - self.level) * smoothing_step(dt, time_constant);
+        self.level
+    }
+}
+
+//Смесь RMS (громкость) и пика (резкие звуки), RMS домножаем на √2, чтобы синус давал 1.0
+fn raw_level(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let (sum, peak) = samples
+        .iter()
+        .fold((0.0_f32, 0.0_f32), |(sum, peak), s| (sum + s * s, peak.max(s.abs())));
+    let rms = (sum / samples.len() as f32).sqrt() * std::f32::consts::SQRT_2;
+
+    (0.7 * rms + 0.3 * peak).min(1.0)
+}
+
+//Доля пути к цели за dt для экспоненциального сглаживания, не зависит от частоты кадров
+pub fn smoothing_step(dt: f32, time_constant: f32) -> f32 {
+    if time_constant <= 0.0 {
+        return 1.0;
+    }
+    1.0 - (-dt / time_constant).exp()
+}
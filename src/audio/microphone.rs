@@ -0,0 +1,54 @@
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, StreamConfig};
+use ringbuf::traits::Producer;
+
+use crate::audio::input::{AudioError, SampleProducer};
+
+//Открываем устройство ввода по умолчанию и сразу запускаем поток
+pub fn open(producer: SampleProducer) -> Result<(cpal::Stream, u32), AudioError> {
+    let host = cpal::default_host();
+    let device = host.default_input_device().ok_or(AudioError::NoDevice)?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| AudioError::Unsupported(e.to_string()))?;
+
+    let format = supported.sample_format();
+    let config: StreamConfig = supported.into();
+    let sample_rate = config.sample_rate.0;
+
+    let stream = match format {
+        SampleFormat::F32 => build::<f32>(&device, &config, producer),
+        SampleFormat::I16 => build::<i16>(&device, &config, producer),
+        SampleFormat::U16 => build::<u16>(&device, &config, producer),
+        SampleFormat::I32 => build::<i32>(&device, &config, producer),
+        other => return Err(AudioError::Unsupported(format!("sample format {other}"))),
+    }
+    .map_err(|e| AudioError::Stream(e.to_string()))?;
+
+    stream.play().map_err(|e| AudioError::Stream(e.to_string()))?;
+    Ok((stream, sample_rate))
+}
+
+fn build<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    mut producer: SampleProducer,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            // В колбэке нельзя блокироваться: лишнее при переполнении просто отбрасываем
+            for frame in data.chunks(channels) {
+                let mono = frame.iter().map(|s| f32::from_sample(*s)).sum::<f32>() / channels as f32;
+                let _ = producer.try_push(mono);
+            }
+        },
+        |err| eprintln!("Audio input error: {err}"),
+        None,
+    )
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use realfft::num_complex::Complex;
+use realfft::{RealFftPlanner, RealToComplex};
+
+use crate::audio::level::smoothing_step;
+
+// Диапазон частот, который раскладываем по кольцу
+const MIN_FREQUENCY: f32 = 40.0;
+const MAX_FREQUENCY: f32 = 16_000.0;
+// Все, что тише, считаем тишиной
+const MIN_DB: f32 = -70.0;
+
+// Спектр с логарифмической разбивкой по полосам, сглаживанием и удержанием пиков
+pub struct Spectrum {
+    fft: Arc<dyn RealToComplex<f32>>,
+    hann: Vec<f32>,
+    input: Vec<f32>,
+    output: Vec<Complex<f32>>,
+    // Границы полос в индексах бинов FFT
+    edges: Vec<(usize, usize)>,
+    bands: Vec<f32>,
+    peaks: Vec<f32>,
+    // Постоянная времени сглаживания полос в секундах
+    smoothing: f32,
+    // Скорость спада удержанных пиков в долях шкалы за секунду
+    peak_decay: f32,
+}
+
+impl Spectrum {
+    pub fn new(fft_size: usize, band_count: usize, sample_rate: u32, smoothing: f32, peak_decay: f32) -> Spectrum {
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(fft_size);
+        let hann = (0..fft_size)
+            .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / fft_size as f32).cos())
+            .collect();
+        let band_count = band_count.max(1);
+
+        Spectrum {
+            input: fft.make_input_vec(),
+            output: fft.make_output_vec(),
+            fft,
+            hann,
+            edges: log_band_edges(fft_size, band_count, sample_rate),
+            bands: vec![0.0; band_count],
+            peaks: vec![0.0; band_count],
+            smoothing,
+            peak_decay,
+        }
+    }
+
+    pub fn bands(&self) -> &[f32] {
+        &self.bands
+    }
+
+    pub fn peaks(&self) -> &[f32] {
+        &self.peaks
+    }
+
+    //Считает спектр по последним fft_size сэмплам окна, dt - время с прошлого обновления
+    pub fn update(&mut self, samples: &[f32], dt: f32) {
+        let size = self.input.len();
+        let start = samples.len().saturating_sub(size);
+        let samples = &samples[start..];
+
+        self.input.fill(0.0);
+        for ((input, sample), weight) in self.input.iter_mut().zip(samples).zip(&self.hann) {
+            *input = sample * weight;
+        }
+        if self.fft.process(&mut self.input, &mut self.output).is_err() {
+            return;
+        }
+
+        // Нормируем так, чтобы синус с амплитудой 1 давал 0 дБ (сумма окна Ханна = N/2)
+        let norm = 4.0 / size as f32;
+        let step = smoothing_step(dt, self.smoothing);
+        for (i, &(lo, hi)) in self.edges.iter().enumerate() {
+            let magnitude = self.output[lo..hi].iter().map(|c| c.norm()).fold(0.0, f32::max) * norm;
+            let db = 20.0 * (magnitude + f32::EPSILON).log10();
+            let value = ((db - MIN_DB) / -MIN_DB).clamp(0.0, 1.0);
+
+            let band = &mut self.bands[i];
+            *band += (value - *band) * step;
+
+            let peak = &mut self.peaks[i];
+            *peak = (*peak - self.peak_decay * dt).max(*band);
+        }
+    }
+}
+
+//Полосы равной ширины в логарифмической шкале частот, минимум один бин на полосу
+fn log_band_edges(fft_size: usize, band_count: usize, sample_rate: u32) -> Vec<(usize, usize)> {
+    let bin_count = fft_size / 2 + 1;
+    let nyquist = sample_rate as f32 / 2.0;
+    let max_frequency = MAX_FREQUENCY.min(nyquist);
+    let ratio = max_frequency / MIN_FREQUENCY;
+    let to_bin = |frequency: f32| (frequency * fft_size as f32 / sample_rate as f32) as usize;
+
+    (0..band_count)
+        .map(|band| {
+            let lo = MIN_FREQUENCY * ratio.powf(band as f32 / band_count as f32);
+            let hi = MIN_FREQUENCY * ratio.powf((band + 1) as f32 / band_count as f32);
+            let lo = to_bin(lo).min(bin_count - 1);
+            let hi = to_bin(hi).clamp(lo + 1, bin_count);
+            (lo, hi)
+        })
+        .collect()
+}
@@ -0,0 +1,91 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use hound::{SampleFormat, WavReader};
+use ringbuf::traits::Producer;
+
+use crate::audio::input::{AudioError, SampleProducer};
+
+// Размер порции, которую отдаем в буфер за один тик
+const CHUNK_MILLIS: u64 = 10;
+
+// Проигрывает WAV-файл в реальном времени вместо микрофона (по кругу)
+pub struct WavPlayer {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+    sample_rate: u32,
+}
+
+impl WavPlayer {
+    pub fn open(path: &Path, mut producer: SampleProducer) -> Result<WavPlayer, AudioError> {
+        let (samples, sample_rate) = read_mono(path)?;
+        if samples.is_empty() {
+            return Err(AudioError::Wav(format!("{} contains no samples", path.display())));
+        }
+
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
+        let chunk = (sample_rate as u64 * CHUNK_MILLIS / 1000).max(1) as usize;
+
+        let handle = thread::spawn(move || {
+            let mut position = 0;
+            let mut next_tick = Instant::now();
+            while flag.load(Ordering::Relaxed) {
+                let end = (position + chunk).min(samples.len());
+                producer.push_slice(&samples[position..end]);
+                position = if end == samples.len() { 0 } else { end };
+
+                next_tick += Duration::from_millis(CHUNK_MILLIS);
+                thread::sleep(next_tick.saturating_duration_since(Instant::now()));
+            }
+        });
+
+        Ok(WavPlayer {
+            running,
+            handle: Some(handle),
+            sample_rate,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+impl Drop for WavPlayer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+//Читаем файл целиком и сводим каналы в моно
+fn read_mono(path: &Path) -> Result<(Vec<f32>, u32), AudioError> {
+    let mut reader = WavReader::open(path).map_err(|e| AudioError::Wav(format!("{}: {e}", path.display())))?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>(),
+        SampleFormat::Int => {
+            let max = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / max))
+                .collect::<Result<_, _>>()
+        }
+    }
+    .map_err(|e| AudioError::Wav(format!("{}: {e}", path.display())))?;
+
+    let mono = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    Ok((mono, spec.sample_rate))
+}
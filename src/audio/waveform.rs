@@ -0,0 +1,48 @@
+// Осциллограмма для кольца: окно выравнивается по переходу через ноль, чтобы форма не дрожала
+pub struct Waveform {
+    span: usize,
+    points: Vec<f32>,
+}
+
+impl Waveform {
+    //span - сколько сэмплов укладываем на окружность, points - на сколько точек разбиваем
+    pub fn new(span: usize, points: usize) -> Waveform {
+        Waveform {
+            span: span.max(1),
+            points: vec![0.0; points],
+        }
+    }
+
+    pub fn points(&self) -> &[f32] {
+        &self.points
+    }
+
+    pub fn reset(&mut self) {
+        self.points.fill(0.0);
+    }
+
+    pub fn update(&mut self, samples: &[f32]) {
+        if samples.len() < self.span {
+            return;
+        }
+        let start = trigger(samples, self.span);
+        let window = &samples[start..start + self.span];
+
+        //Каждая точка - среднее своего участка окна
+        let count = self.points.len();
+        for (i, point) in self.points.iter_mut().enumerate() {
+            let lo = i * self.span / count;
+            let hi = ((i + 1) * self.span / count).max(lo + 1);
+            *point = window[lo..hi].iter().sum::<f32>() / (hi - lo) as f32;
+        }
+    }
+}
+
+//Ищем самый свежий переход через ноль снизу вверх, после которого помещается span сэмплов
+fn trigger(samples: &[f32], span: usize) -> usize {
+    let last = samples.len() - span;
+    (1..=last)
+        .rev()
+        .find(|&i| samples[i - 1] < 0.0 && samples[i] >= 0.0)
+        .unwrap_or(last)
+}
@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use render::ipc::{self, client::Client, protocol::Command};
+use render::ui::lifecycle::AssistantState;
+
+#[derive(Parser)]
+#[command(about = "Control a running Jarvis visualizer over its socket")]
+struct Args {
+    /// Control socket path (defaults to $XDG_RUNTIME_DIR/jarvis.sock)
+    #[arg(long, value_name = "PATH")]
+    socket: Option<PathBuf>,
+    #[command(subcommand)]
+    action: Action,
+}
+
+#[derive(Subcommand)]
+enum Action {
+    /// Switch the assistant state
+    SetState { state: AssistantState },
+    /// Set the playback level (0..1) used while speaking
+    SetLevel { level: f32 },
+    /// Show a caption in the window title (not visible in overlay mode), an empty string hides it
+    ShowCaption { text: String },
+    /// Switch the color theme (purple, cyan, amber, white)
+    SetTheme { theme: String },
+    /// Close the visualizer
+    Quit,
+    /// Print events from the visualizer as JSON lines
+    Listen,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let socket = args.socket.unwrap_or_else(ipc::default_socket_path);
+
+    let mut client = match Client::connect(&socket) {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("Can't connect to {}: {err}", socket.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let command = match args.action {
+        Action::SetState { state } => Command::SetState { state },
+        Action::SetLevel { level } => Command::SetLevel { level },
+        Action::ShowCaption { text } => Command::ShowCaption { text },
+        Action::SetTheme { theme } => Command::SetTheme { theme },
+        Action::Quit => Command::Quit,
+        Action::Listen => return listen(&mut client),
+    };
+
+    match client.send(&command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Can't send command: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn listen(client: &mut Client) -> ExitCode {
+    loop {
+        match client.next_event() {
+            Ok(Some(event)) => println!("{}", serde_json::to_string(&event).unwrap()),
+            Ok(None) => return ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("Can't read event: {err}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+}
@@ -0,0 +1,446 @@
+// Файл настроек в TOML. Все поля необязательны: отсутствующие берутся из значений по умолчанию.
+//
+// [window]     width, height (логические пиксели), title, overlay - прозрачное окно без рамки поверх остальных,
+//              click_through - в режиме overlay пропускать мышь к окнам под ним,
+//              present_mode - "vsync", "mailbox" или "immediate"
+// [theme]      name - начальная тема из [themes], background - цвет фона
+// [themes]     имя = цвет свечения; дополняет встроенные purple, cyan, amber, white
+// [ring]       outer > middle > inner - радиусы колец, scale - базовый масштаб,
+//              dash = [рисуем, пропускаем] в сегментах по 2 градуса, для geometry = "sdf" еще
+//              falloff - степень затухания свечения и gradient - затемнение по кругу 0..1
+// [animation]  speed - множитель скорости анимаций, crossfade - длительность перехода в секундах
+// [render]     antialiasing - "none", "analytic", "smaa", "msaa4" или "msaa8",
+//              geometry - "mesh" (сетка сегментов) или "sdf" (по расстоянию в шейдере),
+//              bloom - свечение ярких мест кадра
+// [bloom.idle] и так для каждого состояния: intensity, threshold, radius - сила, порог яркости
+//              и разброс свечения в этом состоянии
+// [keys]       record, mute, theme, visual_mode, screenshot, debug_overlay, quit - клавиши
+//              ("R", "Space", "F1", "Escape"...) или сочетания ("Ctrl+Shift+S"),
+//              push_to_talk - запись идет, пока зажата клавиша record
+//
+// Цвета задаются как "#rrggbb" или "#rrggbbaa". Полный пример - config.example.toml
+
+pub mod keys;
+pub mod watcher;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use winit::keyboard::{KeyCode, ModifiersState};
+
+use crate::config::keys::{Chord, Key};
+use crate::ui::action::Action;
+use crate::ui::animator::{BASE_SCALE, CROSSFADE_SECONDS};
+use crate::ui::bloom::Bloom;
+use crate::ui::colors::{self, Theme};
+use crate::ui::lifecycle::AssistantState;
+use crate::ui::renderer::RingShape;
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub window: WindowConfig,
+    pub theme: ThemeConfig,
+    pub themes: BTreeMap<String, Color>,
+    pub ring: RingConfig,
+    pub animation: AnimationConfig,
+    pub render: RenderConfig,
+    pub bloom: BloomConfig,
+    pub keys: KeysConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub width: u32,
+    pub height: u32,
+    pub title: String,
+    pub overlay: bool,
+    pub click_through: bool,
+    pub present_mode: PresentMode,
+}
+
+// Как кадры попадают на экран. Недоступный на этой видеокарте режим заменяется ближайшим
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresentMode {
+    // Ждем обновления экрана, без разрывов; есть везде
+    #[default]
+    Vsync,
+    // Без разрывов, но последний готовый кадр заменяет ждущий - меньше задержка
+    Mailbox,
+    // Сразу, возможны разрывы кадра
+    Immediate,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub name: String,
+    pub background: Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RingConfig {
+    pub outer: f32,
+    pub middle: f32,
+    pub inner: f32,
+    pub scale: f32,
+    pub dash: [u32; 2],
+    pub falloff: f32,
+    pub gradient: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnimationConfig {
+    pub speed: f32,
+    pub crossfade: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderConfig {
+    pub antialiasing: Antialiasing,
+    pub geometry: RingGeometry,
+    pub bloom: bool,
+}
+
+// Как рисуется кольцо
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RingGeometry {
+    // Сетка из 180 сегментов, штрихи - пропущенные сегменты
+    #[default]
+    Mesh,
+    // Один квадрат на кольцо, форма считается в шейдере по расстоянию до краев
+    Sdf,
+}
+
+// Сглаживание краев: чем дальше от None, тем дороже для видеокарты
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Antialiasing {
+    None,
+    // Сглаживание в шейдере по расстоянию до контура кольца и концов штрихов, почти бесплатно
+    Analytic,
+    // Постобработка SMAA 1x
+    #[default]
+    Smaa,
+    // Мультисэмплинг; если видеокарта не умеет столько сэмплов - меньше, затем SMAA
+    Msaa4,
+    Msaa8,
+}
+
+impl Antialiasing {
+    //Сэмплов на пиксель в проходе отрисовки, 1 - без мультисэмплинга
+    pub fn sample_count(self) -> u32 {
+        match self {
+            Antialiasing::Msaa4 => 4,
+            Antialiasing::Msaa8 => 8,
+            _ => 1,
+        }
+    }
+}
+
+// Свечение по состояниям ассистента, работает при render.bloom = true
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BloomConfig {
+    pub idle: Bloom,
+    pub listening: Bloom,
+    pub thinking: Bloom,
+    pub speaking: Bloom,
+    pub error: Bloom,
+    pub muted: Bloom,
+}
+
+impl BloomConfig {
+    pub fn state(&self, state: AssistantState) -> Bloom {
+        match state {
+            AssistantState::Idle => self.idle,
+            AssistantState::Listening => self.listening,
+            AssistantState::Thinking => self.thinking,
+            AssistantState::Speaking => self.speaking,
+            AssistantState::Error => self.error,
+            AssistantState::Muted => self.muted,
+        }
+    }
+
+    fn states(&self) -> [(&'static str, Bloom); 6] {
+        [
+            ("idle", self.idle),
+            ("listening", self.listening),
+            ("thinking", self.thinking),
+            ("speaking", self.speaking),
+            ("error", self.error),
+            ("muted", self.muted),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    pub record: Chord,
+    pub mute: Chord,
+    pub theme: Chord,
+    pub visual_mode: Chord,
+    pub screenshot: Chord,
+    pub debug_overlay: Chord,
+    pub quit: Chord,
+    pub push_to_talk: bool,
+}
+
+// Цвет RGBA 0..1, в файле записывается строкой "#rrggbb" или "#rrggbbaa"
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Color(pub [f32; 4]);
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("invalid color `{value}`, expected \"#rrggbb\" or \"#rrggbbaa\"");
+        let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+            return Err(invalid());
+        }
+
+        let mut color = [1.0; 4];
+        for (channel, chunk) in color.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let chunk = std::str::from_utf8(chunk).map_err(|_| invalid())?;
+            *channel = u8::from_str_radix(chunk, 16).map_err(|_| invalid())? as f32 / 255.0;
+        }
+        Ok(Color(color))
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    Parse(toml::de::Error),
+    Invalid { key: String, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "can't read config {}: {err}", path.display()),
+            ConfigError::Parse(err) => write!(f, "invalid config: {err}"),
+            ConfigError::Invalid { key, message } => write!(f, "invalid config: `{key}`: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            width: 400,
+            height: 1080,
+            title: "Jarvis".to_string(),
+            overlay: false,
+            click_through: false,
+            present_mode: PresentMode::Vsync,
+        }
+    }
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        ThemeConfig { name: "purple".to_string(), background: Color(colors::BLACK) }
+    }
+}
+
+impl Default for RingConfig {
+    fn default() -> Self {
+        RingConfig { outer: 0.45, middle: 0.4, inner: 0.3, scale: BASE_SCALE, dash: [15, 15], falloff: 1.0, gradient: 0.0 }
+    }
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        AnimationConfig { speed: 1.0, crossfade: CROSSFADE_SECONDS }
+    }
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        let key = |name| Chord::new(Key::from_name(name).unwrap());
+        KeysConfig {
+            record: key("R"),
+            mute: key("M"),
+            theme: key("T"),
+            visual_mode: key("V"),
+            screenshot: key("F12"),
+            debug_overlay: key("F3"),
+            quit: key("Escape"),
+            push_to_talk: false,
+        }
+    }
+}
+
+impl std::str::FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let config: Config = toml::from_str(text).map_err(ConfigError::Parse)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+        text.parse()
+    }
+
+    //Явно указанный файл обязан существовать, файл по умолчанию - нет
+    pub fn load_or_default(path: Option<&Path>) -> Result<Config, ConfigError> {
+        match path {
+            Some(path) => Config::load(path),
+            None => {
+                let path = default_path();
+                if path.exists() { Config::load(&path) } else { Ok(Config::default()) }
+            }
+        }
+    }
+
+    //Встроенные темы, поверх них - темы из файла
+    pub fn theme(&self, name: &str) -> Option<Theme> {
+        let accent = match self.themes.get(name) {
+            Some(color) => color.0,
+            None => colors::theme(name)?.accent,
+        };
+        Some(Theme { accent, background: self.theme.background.0 })
+    }
+
+    //Сначала встроенные, затем добавленные в файле по алфавиту
+    pub fn theme_names(&self) -> Vec<&str> {
+        let extra = self.themes.keys().map(String::as_str).filter(|name| !colors::THEMES.contains(name));
+        colors::THEMES.iter().copied().chain(extra).collect()
+    }
+
+    pub fn initial_theme(&self) -> Theme {
+        self.theme(&self.theme.name).expect("validated theme name")
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |key: &str, message: String| Err(ConfigError::Invalid { key: key.to_string(), message });
+
+        if self.window.width == 0 {
+            return invalid("window.width", "must be greater than 0".to_string());
+        }
+        if self.window.height == 0 {
+            return invalid("window.height", "must be greater than 0".to_string());
+        }
+        if self.theme(&self.theme.name).is_none() {
+            return invalid("theme.name", format!("unknown theme `{}`", self.theme.name));
+        }
+
+        //NaN и бесконечность отсекает is_finite, поэтому сравнения ниже их уже не видят
+        let positive = |value: f32| value.is_finite() && value > 0.0;
+        let non_negative = |value: f32| value.is_finite() && value >= 0.0;
+
+        let ring = &self.ring;
+        if !positive(ring.inner) {
+            return invalid("ring.inner", format!("must be finite and greater than 0, got {}", ring.inner));
+        }
+        if !(positive(ring.middle) && ring.middle > ring.inner) {
+            return invalid("ring.middle", format!("must be finite and greater than ring.inner ({}), got {}", ring.inner, ring.middle));
+        }
+        if !(positive(ring.outer) && ring.outer > ring.middle) {
+            return invalid("ring.outer", format!("must be finite and greater than ring.middle ({}), got {}", ring.middle, ring.outer));
+        }
+        if !positive(ring.scale) {
+            return invalid("ring.scale", format!("must be finite and greater than 0, got {}", ring.scale));
+        }
+        if ring.dash[0] == 0 {
+            return invalid("ring.dash", "the drawn part of the dash must be at least 1 segment".to_string());
+        }
+        if !positive(ring.falloff) {
+            return invalid("ring.falloff", format!("must be finite and greater than 0, got {}", ring.falloff));
+        }
+        if !(0.0..=1.0).contains(&ring.gradient) {
+            return invalid("ring.gradient", format!("must be between 0 and 1, got {}", ring.gradient));
+        }
+
+        if !positive(self.animation.speed) {
+            return invalid("animation.speed", format!("must be finite and greater than 0, got {}", self.animation.speed));
+        }
+        if !non_negative(self.animation.crossfade) {
+            return invalid("animation.crossfade", format!("must be finite and not negative, got {}", self.animation.crossfade));
+        }
+
+        for (state, bloom) in self.bloom.states() {
+            if !non_negative(bloom.intensity) {
+                return invalid(&format!("bloom.{state}.intensity"), format!("must be finite and not negative, got {}", bloom.intensity));
+            }
+            if !non_negative(bloom.threshold) {
+                return invalid(&format!("bloom.{state}.threshold"), format!("must be finite and not negative, got {}", bloom.threshold));
+            }
+            if !positive(bloom.radius) {
+                return invalid(&format!("bloom.{state}.radius"), format!("must be finite and greater than 0, got {}", bloom.radius));
+            }
+        }
+
+        let keys = self.keys.bindings();
+        for (index, (name, chord, _)) in keys.iter().enumerate() {
+            if let Some((other, _, _)) = keys[..index].iter().find(|(_, other, _)| other == chord) {
+                return invalid(&format!("keys.{name}"), format!("`{chord}` is already bound to `keys.{other}`"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RingConfig {
+    pub fn shape(&self) -> RingShape {
+        RingShape {
+            outer: self.outer,
+            middle: self.middle,
+            inner: self.inner,
+            dash: self.dash,
+            falloff: self.falloff,
+            gradient: self.gradient,
+        }
+    }
+}
+
+impl KeysConfig {
+    //Имя поля в [keys], сочетание и действие
+    pub fn bindings(&self) -> [(&'static str, Chord, Action); 7] {
+        [
+            ("record", self.record, Action::ToggleRecord),
+            ("mute", self.mute, Action::Mute),
+            ("theme", self.theme, Action::CycleTheme),
+            ("visual_mode", self.visual_mode, Action::CycleVisualMode),
+            ("screenshot", self.screenshot, Action::Screenshot),
+            ("debug_overlay", self.debug_overlay, Action::ToggleDebugOverlay),
+            ("quit", self.quit, Action::Quit),
+        ]
+    }
+
+    pub fn action(&self, code: KeyCode, modifiers: ModifiersState) -> Option<Action> {
+        self.bindings()
+            .into_iter()
+            .find(|(_, chord, _)| chord.matches(code, modifiers))
+            .map(|(_, _, action)| action)
+    }
+}
+
+//$XDG_CONFIG_HOME/jarvis/config.toml, иначе ~/.config/jarvis/config.toml
+pub fn default_path() -> PathBuf {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .unwrap_or_default()
+        .join("jarvis")
+        .join("config.toml")
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+
+use serde::Deserialize;
+use winit::keyboard::{KeyCode, ModifiersState};
+
+// Клавиша по имени из файла настроек: буквы, цифры, F1-F12 и основные служебные клавиши
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Key(KeyCode);
+
+const NAMED: [(&str, KeyCode); 16] = [
+    ("Escape", KeyCode::Escape),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Up", KeyCode::ArrowUp),
+    ("Down", KeyCode::ArrowDown),
+    ("Left", KeyCode::ArrowLeft),
+    ("Right", KeyCode::ArrowRight),
+    ("Pause", KeyCode::Pause),
+];
+
+const LETTERS: [KeyCode; 26] = [
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF,
+    KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL,
+    KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR,
+    KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX,
+    KeyCode::KeyY, KeyCode::KeyZ,
+];
+
+const DIGITS: [KeyCode; 10] = [
+    KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+    KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+];
+
+const FUNCTION: [KeyCode; 12] = [
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+];
+
+impl Key {
+    //Регистр не важен: "r", "R", "escape" и "Escape" - одно и то же
+    pub fn from_name(name: &str) -> Option<Key> {
+        if let Some((_, code)) = NAMED.iter().find(|(named, _)| named.eq_ignore_ascii_case(name)) {
+            return Some(Key(*code));
+        }
+
+        let upper = name.to_ascii_uppercase();
+        let mut chars = upper.chars();
+        match (chars.next(), chars.as_str()) {
+            (Some(letter @ 'A'..='Z'), "") => Some(Key(LETTERS[letter as usize - 'A' as usize])),
+            (Some(digit @ '0'..='9'), "") => Some(Key(DIGITS[digit as usize - '0' as usize])),
+            (Some('F'), number) => match number.parse::<usize>() {
+                Ok(number @ 1..=12) => Some(Key(FUNCTION[number - 1])),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> KeyCode {
+        self.0
+    }
+
+    fn name(self) -> String {
+        if let Some((name, _)) = NAMED.iter().find(|(_, code)| *code == self.0) {
+            return name.to_string();
+        }
+        let find = |codes: &[KeyCode]| codes.iter().position(|code| *code == self.0);
+        if let Some(index) = find(&LETTERS) {
+            return ((b'A' + index as u8) as char).to_string();
+        }
+        if let Some(index) = find(&DIGITS) {
+            return index.to_string();
+        }
+        match find(&FUNCTION) {
+            Some(index) => format!("F{}", index + 1),
+            None => format!("{:?}", self.0),
+        }
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Key::from_name(&value).ok_or_else(|| format!("unknown key `{value}`"))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// Клавиша с модификаторами: "Ctrl+Shift+S". Без модификаторов - просто клавиша.
+// Срабатывает только при точно таком же наборе зажатых модификаторов
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Chord {
+    pub modifiers: ModifiersState,
+    pub key: Key,
+}
+
+const MODIFIERS: [(&str, ModifiersState); 4] = [
+    ("Ctrl", ModifiersState::CONTROL),
+    ("Shift", ModifiersState::SHIFT),
+    ("Alt", ModifiersState::ALT),
+    ("Super", ModifiersState::SUPER),
+];
+
+//Другие принятые названия тех же модификаторов
+const MODIFIER_ALIASES: [(&str, ModifiersState); 4] = [
+    ("Control", ModifiersState::CONTROL),
+    ("Option", ModifiersState::ALT),
+    ("Cmd", ModifiersState::SUPER),
+    ("Meta", ModifiersState::SUPER),
+];
+
+impl Chord {
+    pub fn new(key: Key) -> Chord {
+        Chord { modifiers: ModifiersState::empty(), key }
+    }
+
+    pub fn parse(text: &str) -> Result<Chord, String> {
+        let (modifier_names, key_name) = match text.rsplit_once('+') {
+            //Клавиша всегда последняя, перед ней через + модификаторы
+            Some((modifiers, key)) => (Some(modifiers), key),
+            None => (None, text),
+        };
+        let key = Key::from_name(key_name.trim()).ok_or_else(|| format!("unknown key `{}`", key_name.trim()))?;
+
+        let mut modifiers = ModifiersState::empty();
+        for name in modifier_names.into_iter().flat_map(|names| names.split('+')) {
+            let name = name.trim();
+            let (_, modifier) = MODIFIERS
+                .iter()
+                .chain(&MODIFIER_ALIASES)
+                .find(|(known, _)| known.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("unknown modifier `{name}` in `{text}`"))?;
+            modifiers |= *modifier;
+        }
+        Ok(Chord { modifiers, key })
+    }
+
+    pub fn matches(&self, code: KeyCode, modifiers: ModifiersState) -> bool {
+        self.key.code() == code && self.modifiers == modifiers
+    }
+}
+
+impl TryFrom<String> for Chord {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Chord::parse(&value)
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, modifier) in MODIFIERS {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
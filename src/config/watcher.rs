@@ -0,0 +1,68 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use crate::config::{Config, ConfigError};
+
+// Как часто проверяем файл
+const POLL_MILLIS: u64 = 250;
+
+// Следит за файлом настроек и перечитывает его при каждом изменении.
+// Опрашиваем время изменения и размер: так переживаем редакторы, которые сохраняют через переименование
+pub struct Watcher {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+// Отпечаток файла, None - файла нет
+type Stamp = Option<(SystemTime, u64)>;
+
+impl Watcher {
+    //Обработчик вызывается в потоке наблюдателя. Файл может еще не существовать, тогда ждем его появления
+    pub fn start(
+        path: impl Into<PathBuf>,
+        handler: impl Fn(Result<Config, ConfigError>) + Send + 'static,
+    ) -> Watcher {
+        let path = path.into();
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
+        let mut last = stamp(&path);
+
+        let handle = thread::spawn(move || {
+            while flag.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(POLL_MILLIS));
+
+                let current = stamp(&path);
+                if current == last {
+                    continue;
+                }
+                last = current;
+                //Удаление файла не сбрасывает настройки
+                if current.is_some() {
+                    handler(Config::load(&path));
+                }
+            }
+        });
+
+        Watcher {
+            running,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
@@ -0,0 +1,13 @@
+pub mod client;
+pub mod protocol;
+pub mod server;
+
+use std::path::PathBuf;
+
+//Сокет по умолчанию: $XDG_RUNTIME_DIR/jarvis.sock, иначе во временной папке
+pub fn default_socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("jarvis.sock")
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ui::lifecycle::AssistantState;
+
+// Команды от ассистента, по одной JSON-строке на команду
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case")]
+pub enum Command {
+    // Запрещенный переход (AssistantState::can_transition_to) не выполняется, в ответ приходит Error
+    SetState { state: AssistantState },
+    // Громкость 0..1, например озвучки ответа
+    SetLevel { level: f32 },
+    // Подпись в заголовке окна, пустой текст убирает ее. В режиме overlay заголовка не видно,
+    // тогда в ответ приходит Error
+    ShowCaption { text: String },
+    SetTheme { theme: String },
+    Quit,
+}
+
+// События обратно всем подключенным клиентам
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    RecordToggled { recording: bool },
+    WindowClosed,
+    Error { message: String },
+}
+
+impl Event {
+    // Ответ на SetState, который окно отклонило
+    pub fn rejected_state(from: AssistantState, to: AssistantState) -> Event {
+        Event::Error { message: format!("can't go from {from:?} to {to:?}") }
+    }
+}
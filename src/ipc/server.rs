@@ -0,0 +1,153 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::ipc::protocol::{Command, Event};
+
+type Clients = Arc<Mutex<Vec<UnixStream>>>;
+
+// Сколько ждем клиента, который не читает события, прежде чем отключить его
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+
+// Управляющий сокет: принимает команды построчно, рассылает события всем клиентам.
+// События пишет отдельный поток, так что медленный клиент не останавливает отрисовку
+pub struct Server {
+    path: PathBuf,
+    clients: Clients,
+    running: Arc<AtomicBool>,
+    accept: Option<JoinHandle<()>>,
+    events: Option<Sender<String>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    //handler вызывается из потоков соединений для каждой разобранной команды
+    pub fn start<H>(path: &Path, handler: H) -> io::Result<Server>
+    where
+        H: Fn(Command) + Send + Sync + 'static,
+    {
+        let listener = bind(path)?;
+        let clients = Clients::default();
+        let running = Arc::new(AtomicBool::new(true));
+        let handler = Arc::new(handler);
+
+        let accept = {
+            let clients = clients.clone();
+            let running = running.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if !running.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    //Таймаут общий для всех копий сокета, в том числе для ответов на ошибки в serve
+                    if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+                        continue;
+                    }
+                    if let Ok(writer) = stream.try_clone() {
+                        clients.lock().unwrap().push(writer);
+                    }
+                    let handler = handler.clone();
+                    thread::spawn(move || serve(stream, handler.as_ref()));
+                }
+            })
+        };
+
+        let (events, queue) = mpsc::channel();
+        let writer = {
+            let clients = clients.clone();
+            thread::spawn(move || broadcast(queue, &clients))
+        };
+
+        Ok(Server {
+            path: path.to_path_buf(),
+            clients,
+            running,
+            accept: Some(accept),
+            events: Some(events),
+            writer: Some(writer),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    //Ставит событие в очередь на отправку всем клиентам и сразу возвращается
+    pub fn emit(&self, event: &Event) {
+        if let Some(events) = &self.events {
+            let _ = events.send(encode(event));
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        //Будим accept, чтобы поток увидел флаг
+        let _ = UnixStream::connect(&self.path);
+        if let Some(accept) = self.accept.take() {
+            let _ = accept.join();
+        }
+        //Закрытая очередь завершает поток записи, уже отправленные события он успевает дописать
+        self.events = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+        for client in self.clients.lock().unwrap().drain(..) {
+            let _ = client.shutdown(std::net::Shutdown::Both);
+        }
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+//Старый файл сокета от упавшего процесса удаляем, живой - не трогаем
+fn bind(path: &Path) -> io::Result<UnixListener> {
+    match UnixListener::bind(path) {
+        Err(err) if err.kind() == io::ErrorKind::AddrInUse && UnixStream::connect(path).is_err() => {
+            std::fs::remove_file(path)?;
+            UnixListener::bind(path)
+        }
+        result => result,
+    }
+}
+
+//Отвалившихся клиентов и тех, кто не читает дольше WRITE_TIMEOUT, забываем
+fn broadcast(queue: Receiver<String>, clients: &Mutex<Vec<UnixStream>>) {
+    for line in queue {
+        clients
+            .lock()
+            .unwrap()
+            .retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+    }
+}
+
+fn serve(stream: UnixStream, handler: &(dyn Fn(Command) + Send + Sync)) {
+    let Ok(mut writer) = stream.try_clone() else { return };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Command>(&line) {
+            Ok(command) => handler(command),
+            Err(err) => {
+                let error = Event::Error { message: format!("bad command: {err}") };
+                if writer.write_all(encode(&error).as_bytes()).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn encode<T: serde::Serialize>(message: &T) -> String {
+    let mut line = serde_json::to_string(message).expect("protocol messages always serialize");
+    line.push('\n');
+    line
+}
@@ -0,0 +1,4 @@
+pub mod audio;
+pub mod config;
+pub mod ipc;
+pub mod ui;
@@ -1,61 +1,395 @@
-use winit::{dpi::PhysicalSize, event::{ElementState, Event, KeyEvent, WindowEvent}, event_loop::{ControlFlow, EventLoop}, keyboard::{KeyCode, PhysicalKey}};
-use crate::ui::state::State;
+use winit::{dpi::{LogicalSize, PhysicalPosition}, event::{ElementState, Event, KeyEvent, WindowEvent}, event_loop::{ControlFlow, EventLoopBuilder, EventLoopWindowTarget}, keyboard::{ModifiersState, PhysicalKey}, window::{Window, WindowLevel}};
+use std::{cell::Cell, path::{Path, PathBuf}, sync::Mutex, time::{SystemTime, UNIX_EPOCH}};
 
-mod ui;
+use clap::{ArgGroup, Parser};
+use render::{audio::input::InputSource, config::{self, watcher::Watcher, Config, ConfigError}, ipc::{self, protocol::{self, Command}, server::Server}, ui::{action::Action, export::{self, Clip, Format}, headless::{self, Shot}, lifecycle::{AssistantState, VisualMode}, placement::{self, Placement}, setup::SetupError, state::State}};
+
+#[derive(Parser)]
+#[command(about = "Jarvis voice assistant visualizer")]
+#[command(group(ArgGroup::new("output").args(["screenshot", "export"])))]
+struct Args {
+    /// Use a WAV file as the recording input instead of the microphone.
+    /// The microphone needs a build with `--features microphone` (ALSA on Linux)
+    #[arg(long, value_name = "FILE")]
+    wav: Option<PathBuf>,
+    /// Number of log-spaced frequency bands in the spectrum mode
+    #[arg(long, default_value_t = 64)]
+    spectrum_bins: usize,
+    /// Control socket path (defaults to $XDG_RUNTIME_DIR/jarvis.sock)
+    #[arg(long, value_name = "PATH")]
+    socket: Option<PathBuf>,
+    /// Config file (defaults to $XDG_CONFIG_HOME/jarvis/config.toml)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Render a single frame to a PNG file without opening a window, then exit
+    #[arg(long, value_name = "FILE")]
+    screenshot: Option<PathBuf>,
+    /// Assistant state to render with --screenshot or --export
+    #[arg(long, value_enum, default_value_t = AssistantState::Idle, requires = "output")]
+    state: AssistantState,
+    /// How the ring looks while recording, for --screenshot or --export
+    #[arg(long, value_enum, default_value_t = VisualMode::Dashed, requires = "output")]
+    mode: VisualMode,
+    /// Audio level from 0 to 1 that drives the recording ring, for --screenshot or --export
+    #[arg(long, default_value_t = 0.0, value_parser = parse_level, requires = "output")]
+    level: f32,
+    /// Seconds since entering the state, for --screenshot
+    #[arg(long, value_name = "SECONDS", default_value_t = 0.0, requires = "screenshot")]
+    time: f32,
+    #[command(flatten)]
+    export: ExportArgs,
+}
+
+#[derive(clap::Args)]
+#[command(next_help_heading = "Export")]
+struct ExportArgs {
+    /// Record an animation of --state without opening a window, then exit
+    #[arg(long, value_name = "PATH")]
+    export: Option<PathBuf>,
+    /// Output format [default: from the extension: .gif, .png/.apng, none - folder of PNG frames]
+    #[arg(long, value_enum, requires = "export")]
+    format: Option<Format>,
+    /// Clip length
+    #[arg(long, value_name = "SECONDS", default_value_t = 3.0, requires = "export")]
+    duration: f32,
+    /// Frames per second
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..=120), requires = "export")]
+    fps: u32,
+    /// Frame size [default: window size from the config]
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_size, requires = "export")]
+    size: Option<(u32, u32)>,
+    /// Transparent background instead of the theme background
+    #[arg(long, requires = "export")]
+    transparent: bool,
+    /// How many times the clip plays, 0 - forever
+    #[arg(long, default_value_t = 0, requires = "export")]
+    loops: u32,
+}
+
+// События цикла: команды из сокета и перечитанный файл настроек
+enum UserEvent {
+    Command(Command),
+    //Настройки заметно больше команды, в событии держим их в куче
+    Config(Result<Box<Config>, ConfigError>),
+}
 
 pub fn main() {
-    pollster::block_on(
-        run_window()
-    );
+    let args = Args::parse();
+    let source = match args.wav {
+        Some(path) => InputSource::Wav(path),
+        None => InputSource::Microphone,
+    };
+    let socket = args.socket.unwrap_or_else(ipc::default_socket_path);
+    let config = match Config::load_or_default(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+    if let Some(path) = args.screenshot {
+        let shot = Shot { mode: args.mode, level: args.level, ..Shot::new(args.state, args.time) };
+        if let Err(err) = pollster::block_on(headless::screenshot(&path, config, &shot)) {
+            eprintln!("Can't take screenshot: {err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    if let Some(path) = &args.export.export {
+        export_clip(path, &args.export, Shot { mode: args.mode, level: args.level, ..Shot::new(args.state, 0.0) }, config);
+        return;
+    }
+    if !source.is_available() {
+        eprintln!("Built without the `microphone` feature: recording is disabled, rebuild with `--features microphone` or pass --wav <file>");
+    }
+    let config_path = args.config.unwrap_or_else(config::default_path);
+    //Вместо паники с трассировкой - что именно не запустилось
+    if let Err(err) = pollster::block_on(run_window(source, args.spectrum_bins, socket, config, config_path)) {
+        eprintln!("Can't start: {err}");
+        std::process::exit(1);
+    }
 }
-pub async fn run_window() {
-    let event_loop = EventLoop::new().unwrap();
+pub async fn run_window(source: InputSource, spectrum_bins: usize, socket: PathBuf, config: Config, config_path: PathBuf) -> Result<(), SetupError> {
+    let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build().map_err(SetupError::EventLoop)?;
     event_loop.set_control_flow(ControlFlow::Poll);
 
-    let window = winit::window::WindowBuilder::new()
-        .with_inner_size(PhysicalSize::new(400,1080))
-        .with_title("Jarvis")
-        .build(&event_loop)
-        .unwrap();
+    //Команды из сокета приходят в цикл событий как пользовательские события
+    let proxy = Mutex::new(event_loop.create_proxy());
+    let server = match Server::start(&socket, move |command| {
+        let _ = proxy.lock().unwrap().send_event(UserEvent::Command(command));
+    }) {
+        Ok(server) => {
+            println!("Control socket: {}", server.path().display());
+            Some(server)
+        }
+        Err(err) => {
+            eprintln!("Can't open control socket {}: {err}", socket.display());
+            None
+        }
+    };
+    let emit = move |event: protocol::Event| {
+        if let Some(server) = &server {
+            server.emit(&event);
+        }
+    };
+
+    //Изменения файла настроек применяются на лету, с ошибкой остаются прежние настройки
+    let proxy = event_loop.create_proxy();
+    let _watcher = Watcher::start(config_path, move |config| {
+        let _ = proxy.send_event(UserEvent::Config(config.map(Box::new)));
+    });
+
+    let mut title = config.window.title.clone();
+    let mut caption = String::new();
+    let mut size = (config.window.width, config.window.height);
+    let mut keys = config.keys;
+    let mut modifiers = ModifiersState::empty();
+    //Режим overlay: прозрачное окно без рамки поверх остальных, таскается мышью
+    let overlay = config.window.overlay;
+    let mut click_through = config.window.click_through;
+    let placement_path = placement::default_path();
+    let mut placement = Placement::load(&placement_path);
 
-    let mut state = State::new(&window).await;
+    let mut builder = winit::window::WindowBuilder::new()
+        .with_inner_size(LogicalSize::new(config.window.width, config.window.height))
+        .with_title(&title)
+        .with_transparent(overlay)
+        .with_decorations(!overlay)
+        .with_window_level(if overlay { WindowLevel::AlwaysOnTop } else { WindowLevel::Normal });
+    if let Some(Placement { x, y }) = placement {
+        builder = builder.with_position(PhysicalPosition::new(x, y));
+    }
+    let window = builder.build(&event_loop).map_err(SetupError::Window)?;
+    if overlay {
+        set_click_through(&window, click_through);
+    }
+    let initial_placement = placement;
+    //Выход из-за ошибки отрисовки - ненулевой код возврата
+    let failed = Cell::new(false);
+    let failed_frame = &failed;
+
+    let mut state = State::new(&window, source, spectrum_bins, config).await?;
 
     event_loop
         .run(move |event, target| {
             match event {
+                //Свернутое окно не перерисовываем и не крутим цикл впустую
                 Event::AboutToWait => {
-                    state.window().request_redraw();
+                    if state.is_paused() {
+                        target.set_control_flow(ControlFlow::Wait);
+                    }
+                    else {
+                        target.set_control_flow(ControlFlow::Poll);
+                        state.window().request_redraw();
+                    }
+                },
+                Event::UserEvent(UserEvent::Config(Ok(config))) => {
+                    println!("Config reloaded");
+                    if config.window.title != title {
+                        title = config.window.title.clone();
+                        state.window().set_title(&window_title(&title, &caption));
+                    }
+                    if (config.window.width, config.window.height) != size {
+                        size = (config.window.width, config.window.height);
+                        let _ = state.window().request_inner_size(LogicalSize::new(size.0, size.1));
+                    }
+                    keys = config.keys;
+                    if config.window.overlay != overlay {
+                        println!("Restart to switch the overlay mode");
+                    }
+                    if overlay && config.window.click_through != click_through {
+                        click_through = config.window.click_through;
+                        set_click_through(state.window(), click_through);
+                    }
+                    state.apply_config(*config);
+                }
+                Event::UserEvent(UserEvent::Config(Err(err))) => {
+                    eprintln!("{err}; keeping the previous config");
+                }
+                Event::UserEvent(UserEvent::Command(command)) => match command {
+                    Command::SetState { state: next } => {
+                        let previous = state.assistant_state();
+                        if !state.set_assistant_state(next) {
+                            emit(protocol::Event::rejected_state(previous, next));
+                        }
+                    }
+                    Command::SetLevel { level } => state.set_level(level),
+                    //Подпись живет в заголовке окна, а у окна без рамки его не видно
+                    Command::ShowCaption { text } => {
+                        caption = text;
+                        state.window().set_title(&window_title(&title, &caption));
+                        if overlay && !caption.is_empty() {
+                            emit(protocol::Event::Error { message: "captions are shown in the window title, which the overlay mode hides".to_string() });
+                        }
+                    }
+                    Command::SetTheme { theme } => {
+                        if !state.set_theme(&theme) {
+                            emit(protocol::Event::Error { message: format!("unknown theme `{theme}`") });
+                        }
+                    }
+                    Command::Quit => target.exit(),
                 },
+                Event::LoopExiting => {
+                    match placement {
+                        Some(moved) if placement != initial_placement => {
+                            if let Err(err) = moved.save(&placement_path) {
+                                eprintln!("Can't save window position to {}: {err}", placement_path.display());
+                            }
+                        }
+                        _ => {}
+                    }
+                    emit(protocol::Event::WindowClosed);
+                }
                 Event::WindowEvent { window_id: _, event } 
                     => match event {
                         WindowEvent::Resized(new_size) => {
                             state.resize(new_size);
                         }
+                        //Другой монитор: winit сам предлагает физический размер с той же логической величиной,
+                        //он придет следом в Resized
+                        WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                            state.set_scale_factor(scale_factor);
+                        }
                         WindowEvent::RedrawRequested => {
-                            state.render();
+                            if let Err(err) = state.render() {
+                                eprintln!("Can't render: {err}; exiting");
+                                failed_frame.set(true);
+                                target.exit();
+                            }
                         },
+                        WindowEvent::Occluded(occluded) => state.set_occluded(occluded),
+                        WindowEvent::Moved(position) => {
+                            placement = Some(Placement { x: position.x, y: position.y });
+                        }
+                        WindowEvent::CursorMoved { position, .. } => {
+                            state.cursor_moved(Some((position.x, position.y)));
+                        }
+                        WindowEvent::CursorLeft { .. } => state.cursor_moved(None),
+                        WindowEvent::MouseInput { state: ElementState::Pressed, button, .. } => {
+                            match state.mouse_pressed(button) {
+                                //Без рамки окно не за что взять, таскаем за пустое место вокруг кольца
+                                Some(Action::MoveWindow) if overlay => {
+                                    if let Err(err) = state.window().drag_window() {
+                                        eprintln!("Can't drag the window: {err}");
+                                    }
+                                }
+                                Some(action) => perform(action, &mut state, target, &emit),
+                                None => {}
+                            }
+                        }
+                        WindowEvent::CloseRequested => target.exit(),
+                        WindowEvent::ModifiersChanged(new) => modifiers = new.state(),
                         WindowEvent::KeyboardInput {
                             event:
                             KeyEvent {
-                                state: ElementState::Pressed,
-                                physical_key: PhysicalKey::Code(KeyCode::KeyR),
+                                state: key_state,
+                                physical_key: PhysicalKey::Code(code),
+                                repeat: false,
                                 ..
                             },
                         ..
-                        } => {state.start_record();},
-                        WindowEvent::CloseRequested | WindowEvent::KeyboardInput {
-                            event:
-                                KeyEvent {
-                                    state: ElementState::Pressed,
-                                    physical_key: PhysicalKey::Code(KeyCode::Escape),
-                                    ..
-                                },
-                            ..
-                        } => target.exit(),
+                        } => {
+                            let pressed = key_state == ElementState::Pressed;
+                            //Рация: пишем, пока зажата клавиша записи. Отпускание ловим и без модификаторов
+                            if keys.push_to_talk && code == keys.record.key.code() && (!pressed || keys.record.matches(code, modifiers)) {
+                                if state.push_to_talk(pressed) {
+                                    emit_recording(&state, &emit);
+                                }
+                            }
+                            else if pressed {
+                                if let Some(action) = keys.action(code, modifiers) {
+                                    perform(action, &mut state, target, &emit);
+                                }
+                            }
+                        },
                         _ => {}
                 },
                 _ => {}
             }
         })
-        .unwrap();
-}
\ No newline at end of file
+        .map_err(SetupError::EventLoop)?;
+    if failed.get() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+//Общее для клавиш, кликов и пунктов меню. Перетаскивание окна обрабатывает сам цикл событий
+fn perform(action: Action, state: &mut State, target: &EventLoopWindowTarget<UserEvent>, emit: &impl Fn(protocol::Event)) {
+    match action {
+        Action::ToggleRecord => {
+            state.toggle_record();
+            emit_recording(state, emit);
+        }
+        Action::Mute => state.toggle_mute(),
+        Action::CycleTheme => state.cycle_theme(),
+        Action::CycleVisualMode => state.cycle_visual_mode(),
+        Action::Screenshot => {
+            let path = screenshot_path();
+            match state.screenshot(&path) {
+                Ok(()) => println!("Saved screenshot to {}", path.display()),
+                Err(err) => eprintln!("Can't take screenshot: {err}"),
+            }
+        }
+        Action::ToggleDebugOverlay => state.toggle_debug_overlay(),
+        Action::Quit => target.exit(),
+        Action::MoveWindow => {}
+    }
+}
+fn emit_recording(state: &State, emit: &impl Fn(protocol::Event)) {
+    emit(protocol::Event::RecordToggled {
+        recording: state.assistant_state() == AssistantState::Listening,
+    });
+}
+//jarvis-<секунды с 1970>.png в рабочей папке
+fn screenshot_path() -> PathBuf {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    PathBuf::from(format!("jarvis-{seconds}.png"))
+}
+//Из shot берутся состояние, вид записи и громкость, время идет по кадрам клипа
+fn export_clip(path: &Path, args: &ExportArgs, shot: Shot, config: Config) {
+    let Some(format) = args.format.or_else(|| Format::from_path(path)) else {
+        eprintln!("Can't guess the format of {}, pass --format", path.display());
+        std::process::exit(2);
+    };
+    let (width, height) = args.size.unwrap_or((config.window.width, config.window.height));
+    let clip = Clip {
+        state: shot.state,
+        mode: shot.mode,
+        level: shot.level,
+        duration: args.duration,
+        fps: args.fps,
+        width,
+        height,
+        transparent: args.transparent,
+        loops: args.loops,
+    };
+    match pollster::block_on(export::export(path, format, config, &clip)) {
+        Ok(frames) => println!("Wrote {frames} frames to {}", path.display()),
+        Err(err) => {
+            eprintln!("Can't export: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+fn parse_level(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(level) if (0.0..=1.0).contains(&level) => Ok(level),
+        _ => Err(format!("expected a number from 0 to 1, got `{value}`")),
+    }
+}
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("expected WIDTHxHEIGHT, got `{value}`");
+    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+    match (width.parse(), height.parse()) {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(invalid()),
+    }
+}
+fn set_click_through(window: &Window, click_through: bool) {
+    if let Err(err) = window.set_cursor_hittest(!click_through) {
+        eprintln!("Can't change click-through: {err}");
+    }
+}
+fn window_title(title: &str, caption: &str) -> String {
+    if caption.is_empty() { title.to_string() } else { format!("{title} — {caption}") }
+}
@@ -1,6 +1,22 @@
 pub mod state;
-mod vertex_generator;
-mod buffers;
-mod setup;
+pub mod vertex_generator;
+pub mod buffers;
+pub mod setup;
 mod index_generator;
-mod colors;
\ No newline at end of file
+pub mod colors;
+pub mod lifecycle;
+pub mod clock;
+pub mod animator;
+pub mod tween;
+pub mod renderer;
+pub mod headless;
+pub mod export;
+pub mod placement;
+pub mod hit_test;
+pub mod menu;
+pub mod action;
+pub mod overlay;
+pub mod debug;
+pub mod surface;
+pub mod bloom;
+pub mod camera;
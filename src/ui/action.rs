@@ -0,0 +1,15 @@
+// Что пользователь может сделать клавишами и мышью: одна точка выполнения в цикле событий
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    ToggleRecord,
+    Mute,
+    CycleTheme,
+    CycleVisualMode,
+    // Сохранить текущий кадр в PNG
+    Screenshot,
+    // График времени кадров поверх кольца
+    ToggleDebugOverlay,
+    Quit,
+    // Перетаскивание окна без рамки за пустое место
+    MoveWindow,
+}
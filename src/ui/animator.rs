@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use crate::ui::clock::Clock;
+use crate::ui::lifecycle::{AssistantState, Look, RingStyle, VisualMode};
+use crate::ui::tween::{Easing, Pose, Track};
+
+// При масштабе 1 кольцо радиуса 1 касается краев короткой стороны окна
+pub const BASE_SCALE: f32 = 0.8;
+// Длительность перехода между состояниями в секундах
+pub const CROSSFADE_SECONDS: f32 = 1.0 / 3.0;
+
+// Состояние и момент входа в него: анимация вида отсчитывается от entered
+struct Phase {
+    look: Look,
+    entered: f32,
+}
+
+// Слой кадра: scale абсолютный, opacity уже учитывает вес перехода
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub style: RingStyle,
+    pub pose: Pose,
+}
+
+// Что рисовать в текущем кадре: во время перехода уходящий слой гаснет, новый проявляется
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub layers: Vec<Layer>,
+}
+
+// Движение кольца по ключевым кадрам вида текущего состояния, все от времени часов
+pub struct Animator {
+    clock: Box<dyn Clock>,
+    last: Duration,
+    state: AssistantState,
+    current: Phase,
+    previous: Option<Phase>,
+    fade_started: f32,
+    crossfade: Track<f32>,
+    scale: f32,
+    speed: f32,
+}
+
+impl Animator {
+    pub fn new(clock: Box<dyn Clock>, assistant: AssistantState) -> Animator {
+        let last = clock.now();
+        Animator {
+            clock,
+            last,
+            state: assistant,
+            current: Phase { look: assistant.look(), entered: last.as_secs_f32() },
+            previous: None,
+            fade_started: 0.0,
+            crossfade: crossfade(CROSSFADE_SECONDS),
+            scale: BASE_SCALE,
+            speed: 1.0,
+        }
+    }
+
+    //Базовый масштаб кольца
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    //Множитель скорости анимаций состояний, на длительность перехода не влияет
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    pub fn set_crossfade(&mut self, seconds: f32) {
+        self.crossfade = crossfade(seconds);
+    }
+
+    pub fn state(&self) -> AssistantState {
+        self.state
+    }
+
+    //Переход к виду нового состояния. Запрещенный переход игнорируется, тогда false
+    pub fn transition(&mut self, next: AssistantState) -> bool {
+        if !self.state.can_transition_to(next) {
+            return false;
+        }
+        self.state = next;
+        let now = self.clock.now().as_secs_f32();
+        let entered = Phase { look: next.look(), entered: now };
+        self.previous = Some(std::mem::replace(&mut self.current, entered));
+        self.fade_started = now;
+        true
+    }
+
+    //Двигает часы анимации и возвращает прошедшее время в секундах
+    pub fn tick(&mut self) -> f32 {
+        let now = self.clock.now();
+        let dt = now.saturating_sub(self.last).as_secs_f32();
+        self.last = now;
+
+        if now.as_secs_f32() - self.fade_started >= self.crossfade.duration() {
+            self.previous = None;
+        }
+        dt
+    }
+
+    //level - сглаженная громкость 0..1
+    pub fn frame(&self, level: f32, mode: VisualMode) -> Frame {
+        let now = self.last.as_secs_f32();
+        let fade = match self.previous {
+            Some(_) => self.crossfade.sample(now - self.fade_started),
+            None => 1.0,
+        };
+
+        let mut layers = Vec::with_capacity(2);
+        if let Some(previous) = &self.previous {
+            layers.push(self.layer(previous, now, level, mode, 1.0 - fade));
+        }
+        layers.push(self.layer(&self.current, now, level, mode, fade));
+        Frame { layers }
+    }
+
+    fn layer(&self, phase: &Phase, now: f32, level: f32, mode: VisualMode, weight: f32) -> Layer {
+        let look = &phase.look;
+        let reaction = Pose {
+            scale: 1.0 + level * look.level_gain / BASE_SCALE,
+            opacity: weight,
+            ..Pose::default()
+        };
+        let mut pose = look.motion.sample((now - phase.entered) * self.speed).compose(&reaction);
+        pose.scale *= self.scale;
+
+        //Вращение заметно только у штрихового кольца, спектр и осциллограмма стоят на месте
+        let style = look.ring_style(mode);
+        if style != RingStyle::Dashed {
+            pose.rotation = 0.0;
+        }
+        Layer { style, pose }
+    }
+}
+
+fn crossfade(seconds: f32) -> Track<f32> {
+    Track::from(0.0).key(seconds, 1.0, Easing::EaseInOut)
+}
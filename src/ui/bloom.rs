@@ -0,0 +1,310 @@
+// Свечение ярких мест кадра (bloom). Кольца рисуются в HDR-текстуру, из нее выделяются яркие места,
+// уменьшаются по уровням и собираются обратно с размытием; результат прибавляется к кадру до SMAA
+
+use serde::Deserialize;
+use wgpu::{BindGroup, BindGroupLayout, Buffer, CommandEncoder, Device, Queue, RenderPipeline, Sampler, TextureFormat, TextureView};
+use wgpu::util::DeviceExt;
+
+use crate::ui::buffers;
+
+// Формат промежуточных текстур: яркость может быть больше 1
+pub const HDR_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+// Сколько раз уменьшаем вдвое, у маленького окна уровней меньше
+pub const LEVELS: usize = 5;
+
+// Настройки свечения одного состояния
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bloom {
+    // Сколько свечения прибавляется к кадру
+    pub intensity: f32,
+    // Ярче этого (по самому яркому каналу) начинает светиться
+    pub threshold: f32,
+    // Разброс размытия, 1 - обычный
+    pub radius: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom { intensity: 0.8, threshold: 0.6, radius: 1.0 }
+    }
+}
+
+impl Bloom {
+    //Плавный переход к target, t = 1 - сразу target
+    pub fn lerp(self, target: Bloom, t: f32) -> Bloom {
+        let mix = |from: f32, to: f32| from + (to - from) * t;
+        Bloom {
+            intensity: mix(self.intensity, target.intensity),
+            threshold: mix(self.threshold, target.threshold),
+            radius: mix(self.radius, target.radius),
+        }
+    }
+}
+
+// Проход по уровню: откуда читаем и размер текселя источника
+struct Pass {
+    bind_group: BindGroup,
+    //Держим буфер, пока жива bind group
+    _texel: Buffer,
+}
+
+// Текстуры зависят от размера окна и пересоздаются в resize
+struct Targets {
+    scene: TextureView,
+    levels: Vec<TextureView>,
+    extract: Pass,
+    down: Vec<Pass>,
+    //От самого маленького уровня к первому
+    up: Vec<Pass>,
+    composite: Pass,
+}
+
+pub struct BloomChain {
+    extract: RenderPipeline,
+    down: RenderPipeline,
+    up: RenderPipeline,
+    composite: RenderPipeline,
+    filter_layout: BindGroupLayout,
+    composite_layout: BindGroupLayout,
+    sampler: Sampler,
+    settings: Buffer,
+    settings_value: Option<[f32; 4]>,
+    targets: Targets,
+}
+
+impl BloomChain {
+    //format - итоговая текстура, в которую сводится кадр со свечением
+    pub fn new(device: &Device, format: TextureFormat, width: u32, height: u32) -> BloomChain {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("bloom.wgsl"));
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let uniform_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let sampler_entry = wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+        let filter_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[texture_entry(0), sampler_entry, uniform_entry(2), uniform_entry(3)],
+        });
+        let composite_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[texture_entry(0), sampler_entry, uniform_entry(2), uniform_entry(3), texture_entry(4)],
+        });
+
+        //Уровни складываются друг с другом при сборке обратно
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::REPLACE,
+        };
+        let extract = create_pipeline(device, &shader, &filter_layout, "fs_extract", HDR_FORMAT, None);
+        let down = create_pipeline(device, &shader, &filter_layout, "fs_down", HDR_FORMAT, None);
+        let up = create_pipeline(device, &shader, &filter_layout, "fs_up", HDR_FORMAT, Some(additive));
+        let composite = create_pipeline(device, &shader, &composite_layout, "fs_composite", format, None);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let settings = uniform_buffer(device, [0.0; 4]);
+        let targets = create_targets(device, &filter_layout, &composite_layout, &sampler, &settings, width, height);
+
+        BloomChain {
+            extract,
+            down,
+            up,
+            composite,
+            filter_layout,
+            composite_layout,
+            sampler,
+            settings,
+            settings_value: None,
+            targets,
+        }
+    }
+
+    //Сюда рисуются кольца вместо итоговой текстуры
+    pub fn scene(&self) -> &TextureView {
+        &self.targets.scene
+    }
+
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        self.targets = create_targets(device, &self.filter_layout, &self.composite_layout, &self.sampler, &self.settings, width, height);
+    }
+
+    //Выделение, уменьшение, сборка и сведение в output. overlay дорисовывает поверх свечения
+    //то, что светиться не должно (меню, отладочный график)
+    pub fn run(&mut self, encoder: &mut CommandEncoder, queue: &Queue, bloom: &Bloom, output: &TextureView, overlay: impl FnOnce(&mut wgpu::RenderPass<'_>)) {
+        let targets = &self.targets;
+        //Уровни складываются, делим силу на их число, чтобы она не зависела от размера окна
+        let settings = [bloom.threshold, bloom.threshold * 0.5, bloom.radius, bloom.intensity / targets.levels.len() as f32];
+        if self.settings_value != Some(settings) {
+            queue.write_buffer(&self.settings, 0, bytemuck::cast_slice(&settings));
+            self.settings_value = Some(settings);
+        }
+
+        fullscreen(encoder, &self.extract, &targets.extract, &targets.levels[0], wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT));
+        for (pass, target) in targets.down.iter().zip(&targets.levels[1..]) {
+            fullscreen(encoder, &self.down, pass, target, wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT));
+        }
+        for (pass, target) in targets.up.iter().zip(targets.levels.iter().rev().skip(1)) {
+            fullscreen(encoder, &self.up, pass, target, wgpu::LoadOp::Load);
+        }
+
+        let mut rpass = begin(encoder, output, wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT));
+        rpass.set_pipeline(&self.composite);
+        rpass.set_bind_group(0, &targets.composite.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+        overlay(&mut rpass);
+    }
+}
+
+fn create_targets(
+    device: &Device,
+    filter_layout: &BindGroupLayout,
+    composite_layout: &BindGroupLayout,
+    sampler: &Sampler,
+    settings: &Buffer,
+    width: u32,
+    height: u32,
+) -> Targets {
+    let scene = hdr_texture(device, width, height);
+    let mut sizes = vec![((width / 2).max(1), (height / 2).max(1))];
+    while let Some(&(w, h)) = sizes.last() {
+        if sizes.len() == LEVELS || w < 4 || h < 4 {
+            break;
+        }
+        sizes.push((w / 2, h / 2));
+    }
+    let levels: Vec<TextureView> = sizes.iter().map(|&(w, h)| hdr_texture(device, w, h)).collect();
+
+    let pass = |layout: &BindGroupLayout, source: &TextureView, size: (u32, u32), scene: Option<&TextureView>| {
+        let texel = uniform_buffer(device, [1.0 / size.0 as f32, 1.0 / size.1 as f32, 0.0, 0.0]);
+        let mut entries = vec![
+            wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(source) },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(sampler) },
+            wgpu::BindGroupEntry { binding: 2, resource: texel.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 3, resource: settings.as_entire_binding() },
+        ];
+        if let Some(scene) = scene {
+            entries.push(wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::TextureView(scene) });
+        }
+        buffers::count_allocation();
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor { label: None, layout, entries: &entries });
+        Pass { bind_group, _texel: texel }
+    };
+
+    let extract = pass(filter_layout, &scene, (width, height), None);
+    let down = (1..levels.len()).map(|level| pass(filter_layout, &levels[level - 1], sizes[level - 1], None)).collect();
+    let up = (1..levels.len()).rev().map(|level| pass(filter_layout, &levels[level], sizes[level], None)).collect();
+    let composite = pass(composite_layout, &levels[0], sizes[0], Some(&scene));
+
+    Targets { scene, levels, extract, down, up, composite }
+}
+
+fn hdr_texture(device: &Device, width: u32, height: u32) -> TextureView {
+    buffers::count_allocation();
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HDR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+fn uniform_buffer(device: &Device, value: [f32; 4]) -> Buffer {
+    buffers::count_allocation();
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: None,
+        contents: bytemuck::cast_slice(&value),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    })
+}
+
+fn begin<'a>(encoder: &'a mut CommandEncoder, target: &'a TextureView, load: wgpu::LoadOp<wgpu::Color>) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: None,
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: wgpu::Operations { load, store: wgpu::StoreOp::Store },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    })
+}
+
+fn fullscreen(encoder: &mut CommandEncoder, pipeline: &RenderPipeline, pass: &Pass, target: &TextureView, load: wgpu::LoadOp<wgpu::Color>) {
+    let mut rpass = begin(encoder, target, load);
+    rpass.set_pipeline(pipeline);
+    rpass.set_bind_group(0, &pass.bind_group, &[]);
+    rpass.draw(0..3, 0..1);
+}
+
+fn create_pipeline(
+    device: &Device,
+    shader: &wgpu::ShaderModule,
+    layout: &BindGroupLayout,
+    fragment_entry: &str,
+    format: TextureFormat,
+    blend: Option<wgpu::BlendState>,
+) -> RenderPipeline {
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: None,
+        bind_group_layouts: &[layout],
+        push_constant_ranges: &[],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: None,
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: fragment_entry,
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            targets: &[Some(wgpu::ColorTargetState { format, blend, write_mask: wgpu::ColorWrites::ALL })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
@@ -0,0 +1,82 @@
+// Свечение: яркие места кадра выделяются в половинное разрешение, уменьшаются по уровням,
+// собираются обратно с размытием и прибавляются к кадру
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+@group(0) @binding(1)
+var linear_sampler: sampler;
+// xy - размер текселя источника
+@group(0) @binding(2)
+var<uniform> texel: vec4<f32>;
+// порог, мягкость порога, радиус размытия, сила на уровень
+@group(0) @binding(3)
+var<uniform> settings: vec4<f32>;
+// Только для сведения: кадр, к которому прибавляем свечение
+@group(0) @binding(4)
+var scene: texture_2d<f32>;
+
+// Один треугольник на весь экран
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(corner * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2<f32>(corner.x, 1.0 - corner.y);
+    return out;
+}
+
+// Уменьшение вдвое: центр и четыре диагональных соседа
+fn downsample(uv: vec2<f32>) -> vec3<f32> {
+    let offset = texel.xy;
+    var sum = textureSample(source, linear_sampler, uv).rgb * 4.0;
+    sum += textureSample(source, linear_sampler, uv - offset).rgb;
+    sum += textureSample(source, linear_sampler, uv + offset).rgb;
+    sum += textureSample(source, linear_sampler, uv + vec2<f32>(offset.x, -offset.y)).rgb;
+    sum += textureSample(source, linear_sampler, uv - vec2<f32>(offset.x, -offset.y)).rgb;
+    return sum / 8.0;
+}
+
+@fragment
+fn fs_extract(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = downsample(in.uv);
+    //Мягкий порог: чуть ниже порога свечение не обрывается, а плавно гаснет
+    let brightness = max(color.r, max(color.g, color.b));
+    let knee = settings.y;
+    let soft = clamp(brightness - settings.x + knee, 0.0, 2.0 * knee);
+    let contribution = max(soft * soft / (4.0 * knee + 0.0001), brightness - settings.x) / max(brightness, 0.0001);
+    return vec4<f32>(color * contribution, 0.0);
+}
+
+@fragment
+fn fs_down(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(downsample(in.uv), 0.0);
+}
+
+// Увеличение вдвое с размытием по восьми точкам, радиус раздвигает их
+@fragment
+fn fs_up(in: VertexOutput) -> @location(0) vec4<f32> {
+    let offset = texel.xy * settings.z;
+    var sum = textureSample(source, linear_sampler, in.uv + vec2<f32>(-2.0 * offset.x, 0.0)).rgb;
+    sum += textureSample(source, linear_sampler, in.uv + vec2<f32>(2.0 * offset.x, 0.0)).rgb;
+    sum += textureSample(source, linear_sampler, in.uv + vec2<f32>(0.0, -2.0 * offset.y)).rgb;
+    sum += textureSample(source, linear_sampler, in.uv + vec2<f32>(0.0, 2.0 * offset.y)).rgb;
+    sum += textureSample(source, linear_sampler, in.uv + offset).rgb * 2.0;
+    sum += textureSample(source, linear_sampler, in.uv - offset).rgb * 2.0;
+    sum += textureSample(source, linear_sampler, in.uv + vec2<f32>(offset.x, -offset.y)).rgb * 2.0;
+    sum += textureSample(source, linear_sampler, in.uv - vec2<f32>(offset.x, -offset.y)).rgb * 2.0;
+    return vec4<f32>(sum / 12.0, 0.0);
+}
+
+// Кадр с умноженной на альфу прозрачностью: свечение добавляет и цвет, и непрозрачность
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(scene, linear_sampler, in.uv);
+    let glow = textureSample(source, linear_sampler, in.uv).rgb * settings.w;
+    let alpha = clamp(color.a + max(glow.r, max(glow.g, glow.b)), 0.0, 1.0);
+    return vec4<f32>(color.rgb + glow, alpha);
+}
@@ -1,74 +1,297 @@
-use bytemuck::NoUninit;
-use cgmath::Rad;
-use wgpu::{Buffer, Device};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bytemuck::{NoUninit, Zeroable};
+use wgpu::{BindGroup, BindGroupLayout, Buffer, Device, Queue};
 use wgpu::util::DeviceExt;
-use crate::ui::colors::*;
+use crate::ui::camera::{self, Camera};
+use crate::ui::colors::Theme;
 use crate::ui::index_generator;
-use crate::ui::vertex_generator::{self, Vertex};
-
-#[rustfmt::skip]
-pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
-    1.0, 0.0, 0.0, 0.0,
-    0.0, 1.0, 0.0, 0.0,
-    0.0, 0.0, 0.5, 0.5,
-    0.0, 0.0, 0.0, 1.0,
-);
+use crate::ui::overlay;
+use crate::ui::renderer::RingShape;
+use crate::ui::vertex_generator::{self, Outline, Vertex, SEGMENTS};
+
+// Кольцо для отрисовки по расстоянию (vs_sdf/fs_sdf в shader.wgsl), смещения сегментов лежат в отдельном буфере
 #[repr(C)]
-#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct TransformUniform {
-    scale: [[f32; 4]; 4],
-    rotation: [[f32; 4]; 4],
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct RingUniform {
+    //outer, middle, inner, falloff
+    radii: [f32; 4],
+    //рисуем, пропускаем, штрих ли (0 или 1), gradient
+    dash: [f32; 4],
+    accent: [f32; 4],
+    background: [f32; 4],
+    //x - половина стороны квадрата, в котором рисуется кольцо
+    extent: [f32; 4],
 }
 
-pub fn create_vertex(device: &Device) ->  Vec<Buffer> {
+// Запас вокруг кольца, чтобы сглаживание края не обрезалось квадратом
+const SDF_MARGIN: f32 = 0.02;
 
-    let glow_ring_outer = vertex_generator::generate_glow_ring(0.45,0.4, PURPLE, BLACK);
-    let glow_ring_inner = vertex_generator::generate_glow_ring(0.4,0.3, BLACK, PURPLE);
-    
-    vec![
-        get_vertex_buffer(glow_ring_outer, device),
-        get_vertex_buffer(glow_ring_inner, device),
-        ]
+// Счетчик созданных на устройстве буферов, bind group и текстур, чтобы ловить выделения в каждом кадре.
+// Отчет wgpu видит только живые ресурсы, созданный и удаленный в том же кадре буфер в нем не останется
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn allocations() -> usize {
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+pub(crate) fn count_allocation() {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
 }
-pub fn create_index(device: &Device) -> Vec<(Buffer, u32)> {
+
+pub fn create_index(dash: [u32; 2], device: &Device) -> Vec<(Buffer, u32)> {
     let ring = index_generator::generate_ring();
     let ring_len = ring.len() as u32;
 
-    let partial_ring = index_generator::generate_partial_ring();
+    let partial_ring = index_generator::generate_partial_ring(dash);
     let partial_ring_len = partial_ring.len() as u32;
 
     vec![
-        (get_index_buffer(ring, &device), ring_len),
-        (get_index_buffer(partial_ring, &device), partial_ring_len),
+        (get_index_buffer(ring, device), ring_len),
+        (get_index_buffer(partial_ring, device), partial_ring_len),
         ]
 }
-pub fn create_uniform(aspect_ratio:f32, scale:f32, rotation: f32 , device: &Device) -> Vec<Buffer> {
-    let aspect_ratio_uniform = [1.0, aspect_ratio, 1.0, 1.0];
+//Проекция камеры, общая для всех фигур одного конвейера
+pub fn create_camera(camera: &Camera, device: &Device) -> Buffer {
+    get_uniform_buffer::<[[f32; 4]; 4]>(camera.projection().into(), device)
+}
+pub fn write_camera(buffer: &Buffer, camera: &Camera, queue: &Queue) {
+    let projection: [[f32; 4]; 4] = camera.projection().into();
+    queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[projection]));
+}
 
-    let rotation = cgmath::Matrix4::from_angle_z(Rad(rotation)) * OPENGL_TO_WGPU_MATRIX;
-    let scale = cgmath::Matrix4::from_scale(scale) * OPENGL_TO_WGPU_MATRIX;
-    let transform_uniform = TransformUniform {scale: scale.into(), rotation: rotation.into()};
+// Буферы одного слоя кольца: создаются один раз, дальше только перезаписываются,
+// и только если значения изменились
+pub struct LayerBuffers {
+    vertex: [Buffer; 2],
+    model: Buffer,
+    tint: Buffer,
+    edge: Buffer,
+    ring: Buffer,
+    //Смещения сегментов внешнего, затем внутреннего кольца, по четыре в vec4
+    offsets: Buffer,
+    bind_group: BindGroup,
+    geometry: Option<(Theme, RingShape, Vec<f32>, Vec<f32>)>,
+    transform_value: Option<(f32, f32)>,
+    tint_value: Option<[f32; 4]>,
+    edge_value: Option<[f32; 4]>,
+    ring_value: Option<(RingUniform, Vec<f32>, Vec<f32>)>,
+    offsets_value: Vec<[f32; 4]>,
+}
 
-    vec![
-        get_uniform_buffer(aspect_ratio_uniform, &device),
-        get_uniform_buffer(transform_uniform, &device)
-        ]
+impl LayerBuffers {
+    pub fn new(camera: &Buffer, layout: &BindGroupLayout, device: &Device) -> LayerBuffers {
+        let empty = vertex_generator::generate_glow_ring(0.0, 0.0, [0.0; 4], [0.0; 4], &[], Outline::Outer);
+        let vertex = [
+            get_vertex_buffer(empty.clone(), device),
+            get_vertex_buffer(empty, device),
+        ];
+        let model = get_uniform_buffer(model_uniform(1.0, 0.0), device);
+        let tint = get_uniform_buffer([1.0_f32; 4], device);
+        let edge = get_uniform_buffer([0.0_f32; 4], device);
+        let ring = get_uniform_buffer(RingUniform::zeroed(), device);
+        let offsets_value = vec![[0.0_f32; 4]; SEGMENTS / 2];
+        let offsets = get_uniform_array(&offsets_value, device);
+
+        count_allocation();
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera.as_entire_binding(),
+                }, 
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: model.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: tint.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: edge.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: ring.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: offsets.as_entire_binding(),
+                }
+            ],
+            label: None,
+        });
+
+        LayerBuffers {
+            vertex,
+            model,
+            tint,
+            edge,
+            ring,
+            offsets,
+            bind_group,
+            geometry: None,
+            transform_value: None,
+            tint_value: None,
+            edge_value: None,
+            ring_value: None,
+            offsets_value,
+        }
+    }
+
+    pub fn vertex(&self) -> &[Buffer; 2] {
+        &self.vertex
+    }
+
+    pub fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+
+    pub fn write_geometry(&mut self, theme: &Theme, shape: &RingShape, outer_offsets: &[f32], inner_offsets: &[f32], queue: &Queue) {
+        if self.geometry.as_ref().is_some_and(|(t, s, outer, inner)| t == theme && s == shape && outer == outer_offsets && inner == inner_offsets) {
+            return;
+        }
+        let glow_ring_outer = vertex_generator::generate_glow_ring(shape.outer, shape.middle, theme.accent, theme.background, outer_offsets, Outline::Outer);
+        let glow_ring_inner = vertex_generator::generate_glow_ring(shape.middle, shape.inner, theme.background, theme.accent, inner_offsets, Outline::Inner);
+        queue.write_buffer(&self.vertex[0], 0, bytemuck::cast_slice(&glow_ring_outer));
+        queue.write_buffer(&self.vertex[1], 0, bytemuck::cast_slice(&glow_ring_inner));
+        self.geometry = Some((*theme, *shape, outer_offsets.to_vec(), inner_offsets.to_vec()));
+    }
+
+    pub fn write_transform(&mut self, scale: f32, rotation: f32, queue: &Queue) {
+        if self.transform_value == Some((scale, rotation)) {
+            return;
+        }
+        queue.write_buffer(&self.model, 0, bytemuck::cast_slice(&[model_uniform(scale, rotation)]));
+        self.transform_value = Some((scale, rotation));
+    }
+
+    //Цвет слоя, умножается на цвета вершин
+    pub fn write_tint(&mut self, tint: [f32; 4], queue: &Queue) {
+        if self.tint_value == Some(tint) {
+            return;
+        }
+        queue.write_buffer(&self.tint, 0, bytemuck::cast_slice(&[tint]));
+        self.tint_value = Some(tint);
+    }
+
+    //Сглаживание краев в шейдере: включено ли, есть ли у кольца штрихи и рисунок штриха,
+    //по которому шейдер находит их концы
+    pub fn write_edge(&mut self, enabled: bool, dashed: bool, dash: [u32; 2], queue: &Queue) {
+        let flag = |on: bool| if on { 1.0 } else { 0.0 };
+        let edge = [flag(enabled), dash[0] as f32, dash[1] as f32, flag(dashed)];
+        if self.edge_value == Some(edge) {
+            return;
+        }
+        queue.write_buffer(&self.edge, 0, bytemuck::cast_slice(&[edge]));
+        self.edge_value = Some(edge);
+    }
+
+    //Кольцо для RingGeometry::Sdf: вместо вершин форма, цвета и смещения сегментов для шейдера
+    pub fn write_ring(&mut self, theme: &Theme, shape: &RingShape, dashed: bool, outer_offsets: &[f32], inner_offsets: &[f32], queue: &Queue) {
+        let bulge = outer_offsets.iter().chain(inner_offsets).fold(0.0_f32, |bulge, offset| bulge.max(*offset));
+        let ring = RingUniform {
+            radii: [shape.outer, shape.middle, shape.inner, shape.falloff],
+            dash: [shape.dash[0] as f32, shape.dash[1] as f32, if dashed { 1.0 } else { 0.0 }, shape.gradient],
+            accent: theme.accent,
+            background: theme.background,
+            extent: [shape.outer + bulge + SDF_MARGIN, 0.0, 0.0, 0.0],
+        };
+        if self.ring_value.as_ref().is_some_and(|(r, outer, inner)| *r == ring && outer == outer_offsets && inner == inner_offsets) {
+            return;
+        }
+        queue.write_buffer(&self.ring, 0, bytemuck::cast_slice(&[ring]));
+
+        //Пустой срез - нулевые смещения, как у сетки
+        let offset = |offsets: &[f32], segment: usize| offsets.get(segment).copied().unwrap_or(0.0);
+        for (index, value) in self.offsets_value.iter_mut().enumerate() {
+            let (offsets, first) = if index < SEGMENTS / 4 { (outer_offsets, index * 4) } else { (inner_offsets, index * 4 - SEGMENTS) };
+            *value = [0, 1, 2, 3].map(|lane| offset(offsets, first + lane));
+        }
+        queue.write_buffer(&self.offsets, 0, bytemuck::cast_slice(&self.offsets_value));
+        self.ring_value = Some((ring, outer_offsets.to_vec(), inner_offsets.to_vec()));
+    }
+}
+
+// Вершины поверх колец (меню, отладочный график). Буфер на overlay::MAX_VERTICES создается один раз,
+// перезаписывается только при изменении
+pub struct OverlayBuffer {
+    vertex: Buffer,
+    bind_group: BindGroup,
+    value: Vec<Vertex>,
+}
+
+impl OverlayBuffer {
+    //camera - проекция в логических пикселях
+    pub fn new(camera: &Buffer, layout: &BindGroupLayout, device: &Device) -> OverlayBuffer {
+        let empty = vec![Vertex::new([0.0; 3], [0.0; 4]); overlay::MAX_VERTICES];
+        count_allocation();
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera.as_entire_binding(),
+                }
+            ],
+            label: None,
+        });
+        OverlayBuffer { vertex: get_vertex_buffer(empty, device), bind_group, value: Vec::new() }
+    }
+
+    pub fn vertex(&self) -> &Buffer {
+        &self.vertex
+    }
+
+    pub fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+
+    pub fn len(&self) -> u32 {
+        self.value.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    //Лишние вершины отбрасываются
+    pub fn write(&mut self, vertices: &[Vertex], queue: &Queue) {
+        let vertices = &vertices[..vertices.len().min(overlay::MAX_VERTICES)];
+        if self.value == vertices {
+            return;
+        }
+        if !vertices.is_empty() {
+            queue.write_buffer(&self.vertex, 0, bytemuck::cast_slice(vertices));
+        }
+        self.value.clear();
+        self.value.extend_from_slice(vertices);
+    }
+}
+
+fn model_uniform(scale: f32, rotation: f32) -> [[f32; 4]; 4] {
+    camera::model([0.0; 2], rotation, scale).into()
 }
 
 ///////////////////////////////////
 // СОЗДАНИЕ БУФФЕРОВ В УСТРОЙСТВЕ//
 ///////////////////////////////////
 fn get_vertex_buffer(shape: Vec<Vertex>, device: &Device) -> wgpu::Buffer {
+    count_allocation();
     let vertex_buffer = device.create_buffer_init(
         &wgpu::util::BufferInitDescriptor {
             label: None,
             contents: bytemuck::cast_slice(&shape),
-            usage: wgpu::BufferUsages::VERTEX,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         }
     );
     vertex_buffer
 }
 fn get_index_buffer(indices: Vec<u16>, device: &Device) -> wgpu::Buffer {
+    count_allocation();
     let index_buffer = device.create_buffer_init(
         &wgpu::util::BufferInitDescriptor {
             label: None,
@@ -78,7 +301,18 @@ fn get_index_buffer(indices: Vec<u16>, device: &Device) -> wgpu::Buffer {
     );
     index_buffer
 }
+fn get_uniform_array<T: NoUninit>(values: &[T], device: &Device) -> wgpu::Buffer {
+    count_allocation();
+    device.create_buffer_init(
+        &wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(values),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        }
+    )
+}
 fn get_uniform_buffer<T: NoUninit>(uniform: T, device: &Device) -> wgpu::Buffer {
+    count_allocation();
     let uniform_buffer = device.create_buffer_init(
         &wgpu::util::BufferInitDescriptor {
             label: None,
@@ -87,4 +321,4 @@ fn get_uniform_buffer<T: NoUninit>(uniform: T, device: &Device) -> wgpu::Buffer
         }
     );
     uniform_buffer
-}
\ No newline at end of file
+}
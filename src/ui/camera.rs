@@ -0,0 +1,84 @@
+// Проекция на экран. Кольца задаются в нормализованных единицах: ноль в центре окна, y вверх,
+// короткая сторона окна от -1 до 1 - кольцо остается круглым и целиком в окне при любом размере.
+// Меню и графики - в логических пикселях от левого верхнего угла: на экране с масштабом 2
+// они вдвое больше в физических пикселях и выглядят одинаково на любом мониторе
+
+use cgmath::{Matrix4, Rad};
+
+#[rustfmt::skip]
+pub const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.5,
+    0.0, 0.0, 0.0, 1.0,
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    Normalized,
+    // Логические пиксели: физические, деленные на масштаб экрана
+    Pixels,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    width: f32,
+    height: f32,
+    scale_factor: f32,
+    units: Units,
+}
+
+impl Camera {
+    //size - физические пиксели окна
+    pub fn new(size: (u32, u32), scale_factor: f64, units: Units) -> Camera {
+        Camera {
+            width: size.0.max(1) as f32,
+            height: size.1.max(1) as f32,
+            scale_factor: scale_factor as f32,
+            units,
+        }
+    }
+
+    pub fn projection(&self) -> Matrix4<f32> {
+        let ortho = match self.units {
+            Units::Normalized => {
+                let (x, y) = self.half_extent();
+                cgmath::ortho(-x, x, -y, y, -1.0, 1.0)
+            }
+            Units::Pixels => cgmath::ortho(0.0, self.width / self.scale_factor, self.height / self.scale_factor, 0.0, -1.0, 1.0),
+        };
+        OPENGL_TO_WGPU_MATRIX * ortho
+    }
+
+    //Точка окна в физических пикселях от левого верхнего угла - в единицы камеры
+    pub fn unproject(&self, position: (f64, f64)) -> [f32; 2] {
+        let (x, y) = (position.0 as f32, position.1 as f32);
+        match self.units {
+            Units::Normalized => {
+                let (half_x, half_y) = self.half_extent();
+                [(2.0 * x / self.width - 1.0) * half_x, (1.0 - 2.0 * y / self.height) * half_y]
+            }
+            Units::Pixels => [x / self.scale_factor, y / self.scale_factor],
+        }
+    }
+
+    //Сколько нормализованных единиц видно от центра до края по каждой оси
+    fn half_extent(&self) -> (f32, f32) {
+        let short = self.width.min(self.height);
+        (self.width / short, self.height / short)
+    }
+}
+
+// Положение фигуры в единицах камеры: сначала масштаб, затем поворот вокруг центра, затем сдвиг
+pub fn model(translation: [f32; 2], rotation: f32, scale: f32) -> Matrix4<f32> {
+    Matrix4::from_translation(cgmath::vec3(translation[0], translation[1], 0.0))
+        * Matrix4::from_angle_z(Rad(rotation))
+        * Matrix4::from_scale(scale)
+}
+
+//Обратно из единиц камеры в координаты фигуры, для проверки попадания мышью
+pub fn unmodel(point: [f32; 2], translation: [f32; 2], rotation: f32, scale: f32) -> [f32; 2] {
+    let (x, y) = (point[0] - translation[0], point[1] - translation[1]);
+    let (sin, cos) = (-rotation).sin_cos();
+    [(x * cos - y * sin) / scale, (x * sin + y * cos) / scale]
+}
@@ -1,3 +1,55 @@
 pub static BLACK:[f32; 4] = [0.0, 0.0, 0.0, 1.0];
-pub static _WHITE:[f32; 4] = [1.0, 1.0, 1.0, 1.0];
-pub static PURPLE:[f32; 4] = [0.462745098, 0.584313725, 1.0, 1.0];
\ No newline at end of file
+pub static WHITE:[f32; 4] = [1.0, 1.0, 1.0, 1.0];
+pub static PURPLE:[f32; 4] = [0.462_745_1, 0.584_313_7, 1.0, 1.0];
+pub static CYAN:[f32; 4] = [0.298_039_2, 0.898_039_2, 1.0, 1.0];
+pub static AMBER:[f32; 4] = [1.0, 0.701_960_8, 0.247_058_8, 1.0];
+
+// Цвет свечения кольца и фон, в который оно затухает
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub accent: [f32; 4],
+    pub background: [f32; 4],
+}
+
+impl Theme {
+    //Прозрачный фон того же оттенка, что и свечение: кольцо затухает в прозрачность, не темнея
+    pub fn transparent(self) -> Theme {
+        let [r, g, b, _] = self.accent;
+        Theme { accent: self.accent, background: [r, g, b, 0.0] }
+    }
+
+    pub fn to_linear(self) -> Theme {
+        Theme { accent: to_linear(self.accent), background: to_linear(self.background) }
+    }
+}
+
+//Все цвета здесь и в настройках заданы в sRGB, как в любом редакторе. sRGB-текстура ждет от шейдера
+//линейные значения и сама кодирует их обратно, поэтому перед отрисовкой в нее цвета переводим
+pub fn to_linear(color: [f32; 4]) -> [f32; 4] {
+    let [r, g, b, a] = color;
+    [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+}
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+// Встроенные темы в порядке переключения
+pub const THEMES: [&str; 4] = ["purple", "cyan", "amber", "white"];
+
+pub static DEFAULT_THEME: Theme = Theme { accent: PURPLE, background: BLACK };
+
+pub fn theme(name: &str) -> Option<Theme> {
+    let accent = match name {
+        "purple" => PURPLE,
+        "cyan" => CYAN,
+        "amber" => AMBER,
+        "white" => WHITE,
+        _ => return None,
+    };
+    Some(Theme { accent, background: BLACK })
+}
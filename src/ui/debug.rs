@@ -0,0 +1,66 @@
+// Отладочный график времени кадров в левом верхнем углу: столбик на кадр,
+// зеленый - успеваем за 60 Гц, желтый - за 30 Гц, красный - медленнее
+
+use crate::ui::overlay::Quads;
+use crate::ui::vertex_generator::Vertex;
+
+// Сколько последних кадров на графике
+pub const FRAMES: usize = 120;
+
+// Размеры в логических пикселях; высота графика соответствует MAX_TIME
+const MARGIN: f64 = 8.0;
+const BAR: f64 = 2.0;
+const HEIGHT: f64 = 60.0;
+const MAX_TIME: f32 = 0.05;
+const BUDGET: f32 = 1.0 / 60.0;
+
+const BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const LINE: [f32; 4] = [1.0, 1.0, 1.0, 0.5];
+const FAST: [f32; 4] = [0.3, 0.9, 0.4, 1.0];
+const SLOW: [f32; 4] = [1.0, 0.8, 0.2, 1.0];
+const TOO_SLOW: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+
+// Кольцевой буфер времен кадров, в кадре ничего не выделяет
+#[derive(Debug, Clone)]
+pub struct FrameGraph {
+    times: [f32; FRAMES],
+    next: usize,
+    len: usize,
+}
+
+impl Default for FrameGraph {
+    fn default() -> Self {
+        FrameGraph { times: [0.0; FRAMES], next: 0, len: 0 }
+    }
+}
+
+impl FrameGraph {
+    pub fn push(&mut self, dt: f32) {
+        self.times[self.next] = dt;
+        self.next = (self.next + 1) % FRAMES;
+        self.len = (self.len + 1).min(FRAMES);
+    }
+
+    //От старых кадров к новым
+    pub fn times(&self) -> impl Iterator<Item = f32> + '_ {
+        let start = (self.next + FRAMES - self.len) % FRAMES;
+        (0..self.len).map(move |index| self.times[(start + index) % FRAMES])
+    }
+
+    pub fn vertices(&self, scale_factor: f64) -> Vec<Vertex> {
+        let mut quads = Quads::new(scale_factor);
+        let bottom = MARGIN + HEIGHT;
+        quads.rect(MARGIN, MARGIN, FRAMES as f64 * BAR, HEIGHT, BACKGROUND);
+
+        for (index, time) in self.times().enumerate() {
+            let color = if time <= BUDGET { FAST } else if time <= 2.0 * BUDGET { SLOW } else { TOO_SLOW };
+            let height = (time / MAX_TIME).min(1.0) as f64 * HEIGHT;
+            quads.rect(MARGIN + index as f64 * BAR, bottom - height, BAR, height, color);
+        }
+
+        //Линия 60 Гц
+        let budget = (BUDGET / MAX_TIME) as f64 * HEIGHT;
+        quads.rect(MARGIN, bottom - budget, FRAMES as f64 * BAR, 1.0, LINE);
+        quads.into_vertices()
+    }
+}
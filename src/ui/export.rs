@@ -0,0 +1,140 @@
+// Ролики анимации состояний: кадры через внеэкранную отрисовку, на выходе GIF, APNG или папка PNG
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::ui::headless::{Headless, HeadlessError, Image, Shot};
+use crate::ui::lifecycle::{AssistantState, VisualMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Gif,
+    Apng,
+    // Пронумерованные PNG в папке: frame-0000.png, frame-0001.png...
+    PngSequence,
+}
+
+impl Format {
+    //По расширению: .gif - GIF, .png/.apng - APNG, без расширения - папка с кадрами
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("gif") => Some(Format::Gif),
+            Some(ext) if ext.eq_ignore_ascii_case("png") || ext.eq_ignore_ascii_case("apng") => Some(Format::Apng),
+            None => Some(Format::PngSequence),
+            Some(_) => None,
+        }
+    }
+}
+
+// Что и как записывать. loops - сколько раз проигрывать, 0 - бесконечно
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clip {
+    pub state: AssistantState,
+    pub mode: VisualMode,
+    pub level: f32,
+    pub duration: f32,
+    pub fps: u32,
+    pub width: u32,
+    pub height: u32,
+    pub transparent: bool,
+    pub loops: u32,
+}
+
+impl Clip {
+    pub fn frame_count(&self) -> u32 {
+        ((self.duration * self.fps as f32).round() as u32).max(1)
+    }
+
+    fn shot(&self, frame: u32) -> Shot {
+        Shot {
+            state: self.state,
+            time: frame as f32 / self.fps as f32,
+            mode: self.mode,
+            level: self.level,
+        }
+    }
+}
+
+//Возвращает число записанных кадров
+pub async fn export(path: &Path, format: Format, config: Config, clip: &Clip) -> Result<u32, HeadlessError> {
+    let mut headless = Headless::new(config, clip.width, clip.height, false).await?;
+    headless.set_transparent(clip.transparent);
+    let frames = (0..clip.frame_count()).map(|frame| headless.render(&clip.shot(frame)));
+
+    match format {
+        Format::Gif => write_gif(path, clip, frames)?,
+        Format::Apng => write_apng(path, clip, frames)?,
+        Format::PngSequence => write_sequence(path, frames)?,
+    }
+    Ok(clip.frame_count())
+}
+
+fn create(path: &Path) -> Result<BufWriter<File>, HeadlessError> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|err| HeadlessError::Io(path.to_path_buf(), err))
+}
+
+//В GIF всего 256 цветов на кадр и однобитная прозрачность: полупрозрачное свечение станет непрозрачным
+fn write_gif(path: &Path, clip: &Clip, frames: impl Iterator<Item = Image>) -> Result<(), HeadlessError> {
+    let gif_error = |err: gif::EncodingError| HeadlessError::Gif(path.to_path_buf(), err.to_string());
+    let (width, height) = match (u16::try_from(clip.width), u16::try_from(clip.height)) {
+        (Ok(width), Ok(height)) => (width, height),
+        _ => return Err(HeadlessError::Gif(path.to_path_buf(), "GIF frames are limited to 65535x65535".to_string())),
+    };
+
+    let mut encoder = gif::Encoder::new(create(path)?, width, height, &[]).map_err(gif_error)?;
+    //Счетчик повторов в GIF - сколько раз повторить после первого показа, без расширения - один показ
+    match clip.loops {
+        0 => encoder.set_repeat(gif::Repeat::Infinite).map_err(gif_error)?,
+        1 => {}
+        loops => encoder.set_repeat(gif::Repeat::Finite((loops - 1).min(u16::MAX as u32) as u16)).map_err(gif_error)?,
+    }
+
+    //Задержка в сотых долях секунды. Делится на них не каждый fps, поэтому округляем время конца кадра,
+    //а не задержку: при 30 fps выходит 3, 4, 3, 3, 4, 3 и длина клипа не расходится с duration
+    let mut shown = 0;
+    for (index, mut image) in frames.enumerate() {
+        let end = (100.0 * (index + 1) as f64 / clip.fps as f64).round() as u64;
+        let delay = end.saturating_sub(shown).max(1);
+        shown += delay;
+        let mut frame = gif::Frame::from_rgba_speed(width, height, &mut image.pixels, 10);
+        frame.delay = delay as u16;
+        frame.dispose = gif::DisposalMethod::Background;
+        encoder.write_frame(&frame).map_err(gif_error)?;
+    }
+    Ok(())
+}
+
+fn write_apng(path: &Path, clip: &Clip, frames: impl Iterator<Item = Image>) -> Result<(), HeadlessError> {
+    let png_error = |err: png::EncodingError| HeadlessError::Png(path.to_path_buf(), err.to_string());
+
+    let mut encoder = png::Encoder::new(create(path)?, clip.width, clip.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(clip.frame_count(), clip.loops).map_err(png_error)?;
+    encoder.set_frame_delay(1, clip.fps.min(u16::MAX as u32) as u16).map_err(png_error)?;
+    //Каждый кадр целиком заменяет предыдущий, иначе прозрачные кадры накладываются друг на друга
+    encoder.set_blend_op(png::BlendOp::Source).map_err(png_error)?;
+    encoder.set_dispose_op(png::DisposeOp::Background).map_err(png_error)?;
+
+    let mut writer = encoder.write_header().map_err(png_error)?;
+    for image in frames {
+        writer.write_image_data(&image.pixels).map_err(png_error)?;
+    }
+    writer.finish().map_err(png_error)
+}
+
+fn write_sequence(dir: &Path, frames: impl Iterator<Item = Image>) -> Result<(), HeadlessError> {
+    std::fs::create_dir_all(dir).map_err(|err| HeadlessError::Io(dir.to_path_buf(), err))?;
+    for (index, image) in frames.enumerate() {
+        image.save_png(&frame_path(dir, index))?;
+    }
+    Ok(())
+}
+
+pub fn frame_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("frame-{index:04}.png"))
+}
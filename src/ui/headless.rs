@@ -0,0 +1,281 @@
+// Отрисовка без окна: кадр кольца рисуется в текстуру, читается обратно и сохраняется в PNG
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use wgpu::{Buffer, Device, Queue, Texture, TextureFormat, TextureView};
+
+use crate::config::{Config, RenderConfig};
+use crate::ui::{animator::Animator, clock::ManualClock, colors, lifecycle::{AssistantState, VisualMode}, renderer::{self, Renderer, RingShape, Scene}, setup::{self, SetupError}};
+
+// Формат внеэкранной цели: байты пикселей сразу идут в PNG без перестановки каналов,
+// sRGB - как и у окна, чтобы снимки совпадали с тем, что на экране
+pub const FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+#[derive(Debug)]
+pub enum HeadlessError {
+    NoAdapter,
+    // Адаптер есть, но устройство на нем не создалось
+    Setup(SetupError),
+    // Кадр больше, чем устройство умеет в одной текстуре
+    TooLarge { width: u32, height: u32, limit: u32 },
+    Io(PathBuf, std::io::Error),
+    Png(PathBuf, String),
+    Gif(PathBuf, String),
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeadlessError::NoAdapter => write!(f, "no graphics adapter available, not even a software one"),
+            HeadlessError::Setup(err) => write!(f, "{err}"),
+            HeadlessError::TooLarge { width, height, limit } => {
+                write!(f, "frame size {width}x{height} is too large, the graphics device allows at most {limit} pixels per side")
+            }
+            HeadlessError::Io(path, err) => write!(f, "{}: {err}", path.display()),
+            HeadlessError::Png(path, msg) | HeadlessError::Gif(path, msg) => write!(f, "{}: {msg}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for HeadlessError {}
+
+impl From<SetupError> for HeadlessError {
+    fn from(err: SetupError) -> Self {
+        match err {
+            SetupError::NoAdapter => HeadlessError::NoAdapter,
+            err => HeadlessError::Setup(err),
+        }
+    }
+}
+
+// Какой кадр рисовать: состояние, время с момента входа в него, режим и громкость
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shot {
+    pub state: AssistantState,
+    pub time: f32,
+    pub mode: VisualMode,
+    pub level: f32,
+}
+
+impl Shot {
+    pub fn new(state: AssistantState, time: f32) -> Shot {
+        Shot { state, time, mode: VisualMode::Dashed, level: 0.0 }
+    }
+}
+
+// Пиксели RGBA8 построчно сверху вниз
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn save_png(&self, path: &Path) -> Result<(), HeadlessError> {
+        let file = File::create(path).map_err(|err| HeadlessError::Io(path.to_path_buf(), err))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(|err| HeadlessError::Png(path.to_path_buf(), err.to_string()))
+    }
+
+    //Читает только 8-битные RGBA, в таком виде их пишет save_png
+    pub fn load_png(path: &Path) -> Result<Image, HeadlessError> {
+        let file = File::open(path).map_err(|err| HeadlessError::Io(path.to_path_buf(), err))?;
+        let png_error = |msg: String| HeadlessError::Png(path.to_path_buf(), msg);
+
+        let mut reader = png::Decoder::new(BufReader::new(file)).read_info().map_err(|err| png_error(err.to_string()))?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).map_err(|err| png_error(err.to_string()))?;
+        if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+            return Err(png_error(format!("expected 8-bit RGBA, got {:?} {:?}", info.color_type, info.bit_depth)));
+        }
+        pixels.truncate(info.buffer_size());
+        Ok(Image { width: info.width, height: info.height, pixels })
+    }
+}
+
+// Текстура, в которую рисуем, и буфер, через который читаем ее обратно
+pub struct Offscreen {
+    texture: Texture,
+    view: TextureView,
+    readback: Buffer,
+    padded_row: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Offscreen {
+    pub fn new(device: &Device, width: u32, height: u32) -> Offscreen {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        //Строки при копировании в буфер выравниваются по 256 байт
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = (width * 4).div_ceil(align) * align;
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (padded_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Offscreen { texture, view, readback, padded_row, width, height }
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    pub fn read_pixels(&self, device: &Device, queue: &Queue) -> Image {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = self.readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| result.expect("map readback buffer"));
+        device.poll(wgpu::Maintain::Wait);
+
+        let row = (self.width * 4) as usize;
+        let pixels = {
+            let mapped = slice.get_mapped_range();
+            mapped
+                .chunks(self.padded_row as usize)
+                .flat_map(|padded| &padded[..row])
+                .copied()
+                .collect()
+        };
+        self.readback.unmap();
+
+        Image { width: self.width, height: self.height, pixels }
+    }
+}
+
+// Устройство, рендерер и внеэкранная цель; создаются один раз и переиспользуются для всех кадров
+pub struct Headless {
+    device: Device,
+    queue: Queue,
+    renderer: Renderer,
+    target: Offscreen,
+    config: Config,
+    transparent: bool,
+}
+
+impl Headless {
+    //software - рисовать программным адаптером, одинаково на любой машине
+    pub async fn new(config: Config, width: u32, height: u32, software: bool) -> Result<Headless, HeadlessError> {
+        let (adapter, device, queue) = setup::headless(software).await?;
+        let limit = device.limits().max_texture_dimension_2d;
+        if width > limit || height > limit {
+            return Err(HeadlessError::TooLarge { width, height, limit });
+        }
+        let target = Offscreen::new(&device, width, height);
+        let antialiasing = setup::antialiasing(&adapter, &device, renderer::scene_format(FORMAT, &config.render), config.render.antialiasing);
+        let render = RenderConfig { antialiasing, ..config.render };
+        let renderer = Renderer::new(&device, &queue, FORMAT, width, height, config.ring.shape(), render);
+
+        Ok(Headless {
+            device,
+            queue,
+            renderer,
+            target,
+            config,
+            transparent: false,
+        })
+    }
+
+    //Прозрачный фон вместо цвета темы
+    pub fn set_transparent(&mut self, transparent: bool) {
+        self.transparent = transparent;
+    }
+
+    pub fn render(&mut self, shot: &Shot) -> Image {
+        //Состояние начинается в нуле, часы сразу переводим на нужный момент
+        let clock = ManualClock::new();
+        let mut animator = Animator::new(Box::new(clock.clone()), shot.state);
+        animator.set_scale(self.config.ring.scale);
+        animator.set_speed(self.config.animation.speed);
+        animator.set_crossfade(self.config.animation.crossfade);
+        clock.set(Duration::from_secs_f32(shot.time.max(0.0)));
+        animator.tick();
+
+        let frame = animator.frame(shot.level, shot.mode);
+        let mut theme = self.config.initial_theme();
+        if self.transparent {
+            theme = theme.transparent();
+        }
+        let mut scene = Scene::from_frame(theme, &frame, (&[], &[]));
+        scene.bloom = self.config.bloom.state(shot.state);
+        self.renderer.render(&self.device, &self.queue, self.target.view(), &scene);
+
+        let mut image = self.target.read_pixels(&self.device, &self.queue);
+        if self.transparent {
+            unpremultiply(&mut image.pixels);
+        }
+        image
+    }
+}
+
+//Готовую сцену из окна рисуем заново во внеэкранную текстуру на том же устройстве:
+//кадр поверхности окна прочитать нельзя. Сглаживание в render - уже проверенное для этого адаптера
+pub fn capture(device: &Device, queue: &Queue, scene: &Scene, shape: RingShape, render: RenderConfig, size: (u32, u32)) -> Image {
+    let target = Offscreen::new(device, size.0, size.1);
+    let mut renderer = Renderer::new(device, queue, FORMAT, size.0, size.1, shape, render);
+    renderer.render(device, queue, target.view(), scene);
+
+    let mut image = target.read_pixels(device, queue);
+    if scene.theme.background[3] < 1.0 {
+        unpremultiply(&mut image.pixels);
+    }
+    image
+}
+
+//Смешивание оставляет в текстуре цвет, умноженный на альфу, а PNG ждет цвет как есть.
+//Умножение было в линейном цвете, поэтому и делим в нем, а не в байтах sRGB
+fn unpremultiply(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_mut(4) {
+        let alpha = pixel[3] as f32 / 255.0;
+        if alpha == 0.0 {
+            continue;
+        }
+        for channel in &mut pixel[..3] {
+            let linear = colors::srgb_to_linear(*channel as f32 / 255.0) / alpha;
+            *channel = (colors::linear_to_srgb(linear.min(1.0)) * 255.0).round() as u8;
+        }
+    }
+}
+
+//Один кадр в PNG размером с окно из настроек
+pub async fn screenshot(path: &Path, config: Config, shot: &Shot) -> Result<(), HeadlessError> {
+    let (width, height) = (config.window.width, config.window.height);
+    let mut headless = Headless::new(config, width, height, false).await?;
+    headless.render(shot).save_png(path)
+}
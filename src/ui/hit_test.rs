@@ -0,0 +1,31 @@
+// Попадание курсора в кольцо с учетом текущего масштаба, поворота и формы слоя
+
+use std::f32::consts::TAU;
+
+use crate::ui::camera::{self, Camera, Units};
+use crate::ui::renderer::{Ring, RingShape, SceneLayer};
+use crate::ui::vertex_generator::SEGMENTS;
+
+//position - координаты курсора в пикселях окна от левого верхнего угла
+pub fn hit_ring(layer: &SceneLayer, shape: &RingShape, position: (f64, f64), size: (u32, u32)) -> bool {
+    if size.0 == 0 || size.1 == 0 {
+        return false;
+    }
+    let point = Camera::new(size, 1.0, Units::Normalized).unproject(position);
+    let [x, y] = camera::unmodel(point, [0.0; 2], layer.rotation, layer.scale);
+
+    let radius = x.hypot(y);
+    let degrees = y.atan2(x).rem_euclid(TAU).to_degrees();
+    //Сегмент по 2 градуса: смещения задаются на каждый, штрих рисуется целыми сегментами
+    let segment = (degrees / 2.0) as usize % SEGMENTS;
+    let offset = |offsets: &[f32]| offsets.get(segment).copied().unwrap_or(0.0);
+
+    let inside = radius >= shape.inner + offset(&layer.inner_offsets) && radius <= shape.outer + offset(&layer.outer_offsets);
+    inside && (layer.ring == Ring::Full || dash_drawn(shape.dash, degrees))
+}
+
+//Тот же рисунок, что и в index_generator::generate_partial_ring
+fn dash_drawn(dash: [u32; 2], degrees: f32) -> bool {
+    let segment = ((degrees + 1.0) / 2.0) as u32;
+    segment % (dash[0] + dash[1]) < dash[0]
+}
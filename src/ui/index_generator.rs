@@ -15,18 +15,17 @@ pub fn generate_ring() -> Vec<u16> {
 
     indices
 }
-pub fn generate_partial_ring() -> Vec<u16> {
+//dash - сколько сегментов рисуем и сколько пропускаем, первый сегмент рисуется всегда
+pub fn generate_partial_ring(dash: [u32; 2]) -> Vec<u16> {
 
     let mut indices: Vec<u16> = Vec::new();
 
     indices.extend_from_slice(&[0, 1, 2, 0, 2, 3]);
-    let mut is_draw = true;
+    let period = dash[0] + dash[1];
 
     for i in (2..362).step_by(2) {
-        if i % 30 == 0 {
-            is_draw = !is_draw;
-        }
-        if is_draw {
+        let segment = i as u32 / 2;
+        if segment % period < dash[0] {
             indices.push(i + 1);
             indices.push(i);
             indices.push(i + 2);
@@ -0,0 +1,147 @@
+// Жизненный цикл ассистента и то, как каждое состояние выглядит на экране
+
+use std::f32::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ui::animator::BASE_SCALE;
+use crate::ui::tween::{Animation, Easing, Repeat, Track};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AssistantState {
+    Idle,
+    Listening,
+    Thinking,
+    Speaking,
+    Error,
+    Muted,
+}
+
+impl AssistantState {
+    //Разрешенные переходы: в ошибку можно из любого состояния, из Muted и Error - только в Idle
+    pub fn can_transition_to(self, next: AssistantState) -> bool {
+        use AssistantState::*;
+
+        match (self, next) {
+            (current, next) if current == next => false,
+            (_, Error) => true,
+            (Muted | Error, Idle) => true,
+            (Muted | Error, _) => false,
+            (_, Muted) => true,
+            (Idle, _) => true,
+            (Listening, Idle | Thinking) => true,
+            (Thinking, Idle | Listening | Speaking) => true,
+            (Speaking, Idle | Listening) => true,
+            _ => false,
+        }
+    }
+
+    pub fn look(self) -> Look {
+        match self {
+            AssistantState::Idle => Look {
+                style: RingStyle::Solid,
+                level_gain: 0.0,
+                motion: Animation::new().scale(breathing(0.17, 5.0)),
+            },
+            AssistantState::Listening => Look {
+                style: RingStyle::Audio,
+                level_gain: 0.29,
+                motion: Animation::new()
+                    .rotation(spin(1.8))
+                    .radius(Track::from(-0.03).key(0.6, 0.0, Easing::Elastic)),
+            },
+            AssistantState::Thinking => Look {
+                style: RingStyle::Dashed,
+                level_gain: 0.0,
+                motion: Animation::new()
+                    .color(Track::constant([1.2, 0.8, 1.0, 1.0]))
+                    .rotation(spin(4.8))
+                    .compose(Animation::new().scale(breathing(0.06, 1.0))),
+            },
+            AssistantState::Speaking => Look {
+                style: RingStyle::Solid,
+                level_gain: 0.29,
+                motion: Animation::new()
+                    .color(Track::constant([0.8, 1.2, 1.0, 1.0]))
+                    .scale(breathing(0.09, 2.0 / 3.0)),
+            },
+            //Вздрагивает при входе и мигает
+            AssistantState::Error => Look {
+                style: RingStyle::Solid,
+                level_gain: 0.0,
+                motion: Animation::new()
+                    .color(Track::constant([1.8, 0.45, 0.35, 1.0]))
+                    .scale(Track::from(1.1).key(0.8, 1.0, Easing::Spring { damping: 6.0, frequency: 3.0 }))
+                    .compose(Animation::new().opacity(
+                        Track::from(1.0).key(0.5, 0.6, Easing::EaseInOut).repeat(Repeat::PingPong),
+                    )),
+            },
+            AssistantState::Muted => Look {
+                style: RingStyle::Solid,
+                level_gain: 0.0,
+                motion: Animation::new()
+                    .color(Track::constant([0.5, 0.5, 0.5, 1.0]))
+                    .opacity(Track::constant(0.6))
+                    .radius(Track::from(0.0).key(0.4, -0.03, Easing::CubicOut)),
+            },
+        }
+    }
+}
+
+//Дыхание: масштаб растет на depth за half_period секунд и возвращается обратно
+fn breathing(depth: f32, half_period: f32) -> Track<f32> {
+    Track::from(1.0)
+        .key(half_period, 1.0 + depth / BASE_SCALE, Easing::EaseInOut)
+        .repeat(Repeat::PingPong)
+}
+
+//Равномерное вращение, speed в радианах в секунду
+fn spin(speed: f32) -> Track<f32> {
+    Track::from(0.0).key(TAU / speed, TAU, Easing::Linear).repeat(Repeat::Loop)
+}
+
+//Как выглядит кольцо во время записи
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum VisualMode {
+    Dashed,
+    Spectrum,
+    Waveform,
+}
+
+impl VisualMode {
+    pub fn next(self) -> VisualMode {
+        match self {
+            VisualMode::Dashed => VisualMode::Spectrum,
+            VisualMode::Spectrum => VisualMode::Waveform,
+            VisualMode::Waveform => VisualMode::Dashed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RingStyle {
+    Solid,
+    Dashed,
+    // Зависит от выбранного VisualMode
+    Audio,
+}
+
+#[derive(Debug, Clone)]
+pub struct Look {
+    pub style: RingStyle,
+    // Прибавка к масштабу при максимальной громкости
+    pub level_gain: f32,
+    // Анимация свойств кольца, время отсчитывается от входа в состояние
+    pub motion: Animation,
+}
+
+impl Look {
+    //Стиль Audio раскрывается в зависимости от выбранного режима визуализации
+    pub fn ring_style(&self, mode: VisualMode) -> RingStyle {
+        match (self.style, mode) {
+            (RingStyle::Audio, VisualMode::Dashed) => RingStyle::Dashed,
+            (style, _) => style,
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// Контекстное меню по правому клику. Рисуется прямо в окне: прямоугольники и буквы
+// из пиксельного шрифта 5x7, без системных меню
+
+use crate::ui::overlay::Quads;
+use crate::ui::vertex_generator::Vertex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    Mute,
+    Theme,
+    Quit,
+}
+
+const ITEMS: [MenuItem; 3] = [MenuItem::Mute, MenuItem::Theme, MenuItem::Quit];
+
+// Размеры в логических пикселях
+const WIDTH: f64 = 120.0;
+const ITEM_HEIGHT: f64 = 28.0;
+const PADDING: f64 = 12.0;
+// Размер одного пикселя шрифта
+const DOT: f64 = 2.0;
+
+const BACKGROUND: [f32; 4] = [0.08, 0.08, 0.1, 0.92];
+const TEXT: [f32; 4] = [0.92, 0.92, 0.95, 1.0];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Menu {
+    origin: (f64, f64),
+    hovered: Option<MenuItem>,
+    muted: bool,
+}
+
+impl Menu {
+    //Открывается в точке клика, но не вылезает за край окна. muted меняет подпись первого пункта.
+    //Точки и размер окна - в логических пикселях
+    pub fn open(position: (f64, f64), size: (u32, u32), muted: bool) -> Menu {
+        let height = ITEM_HEIGHT * ITEMS.len() as f64;
+        let x = position.0.min(size.0 as f64 - WIDTH).max(0.0);
+        let y = position.1.min(size.1 as f64 - height).max(0.0);
+        Menu { origin: (x, y), hovered: None, muted }
+    }
+
+    pub fn item_at(&self, position: (f64, f64)) -> Option<MenuItem> {
+        let (x, y) = (position.0 - self.origin.0, position.1 - self.origin.1);
+        if !(0.0..WIDTH).contains(&x) || y < 0.0 {
+            return None;
+        }
+        ITEMS.get((y / ITEM_HEIGHT) as usize).copied()
+    }
+
+    pub fn hover(&mut self, position: Option<(f64, f64)>) {
+        self.hovered = position.and_then(|position| self.item_at(position));
+    }
+
+    //Треугольники в логических пикселях окна, accent подсвечивает пункт под курсором
+    pub fn vertices(&self, accent: [f32; 4], scale_factor: f64) -> Vec<Vertex> {
+        let mut quads = Quads::new(scale_factor);
+        let (x, y) = self.origin;
+        quads.rect(x, y, WIDTH, ITEM_HEIGHT * ITEMS.len() as f64, BACKGROUND);
+
+        for (index, item) in ITEMS.iter().enumerate() {
+            let top = y + ITEM_HEIGHT * index as f64;
+            if self.hovered == Some(*item) {
+                let [r, g, b, _] = accent;
+                quads.rect(x, top, WIDTH, ITEM_HEIGHT, [r, g, b, 0.35]);
+            }
+            let baseline = top + (ITEM_HEIGHT - 7.0 * DOT) / 2.0;
+            text(&mut quads, x + PADDING, baseline, self.label(*item));
+        }
+        quads.into_vertices()
+    }
+
+    fn label(&self, item: MenuItem) -> &'static str {
+        match item {
+            MenuItem::Mute if self.muted => "UNMUTE",
+            MenuItem::Mute => "MUTE",
+            MenuItem::Theme => "THEME",
+            MenuItem::Quit => "QUIT",
+        }
+    }
+}
+
+//Строка пиксельным шрифтом, x и y - левый верхний угол первой буквы
+fn text(quads: &mut Quads, x: f64, y: f64, text: &str) {
+    for (index, letter) in text.chars().enumerate() {
+        let left = x + index as f64 * 6.0 * DOT;
+        for (row, bits) in glyph(letter).iter().enumerate() {
+            for column in 0..5 {
+                if bits & (0b10000 >> column) != 0 {
+                    quads.rect(left + column as f64 * DOT, y + row as f64 * DOT, DOT, DOT, TEXT);
+                }
+            }
+        }
+    }
+}
+
+//Только буквы подписей меню, строки сверху вниз, старший из пяти битов - левый столбец
+fn glyph(letter: char) -> [u8; 7] {
+    match letter {
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001, 0b10001],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        _ => [0; 7],
+    }
+}
@@ -0,0 +1,41 @@
+// Плоские прямоугольники поверх колец: контекстное меню и отладочный график.
+// Координаты в логических пикселях окна от левого верхнего угла, в экран их переводит камера
+
+use crate::ui::vertex_generator::Vertex;
+
+// Размер буфера вершин: меню с самыми длинными подписями и полный график кадров помещаются вместе
+pub const MAX_VERTICES: usize = 4096;
+
+pub struct Quads {
+    vertices: Vec<Vertex>,
+    scale_factor: f64,
+}
+
+impl Quads {
+    //scale_factor - сколько физических пикселей в логическом
+    pub fn new(scale_factor: f64) -> Quads {
+        Quads { vertices: Vec::new(), scale_factor }
+    }
+
+    //Края прижимаются к сетке физических пикселей, а тонкие линии не тоньше одного физического пикселя:
+    //без сглаживания на дробном масштабе иначе они то размываются, то пропадают
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, color: [f32; 4]) {
+        let (x, width) = self.snap(x, width);
+        let (y, height) = self.snap(y, height);
+        let point = |x: f64, y: f64| Vertex::new([x as f32, y as f32, 0.0], color);
+        let corners = [point(x, y), point(x + width, y), point(x + width, y + height), point(x, y + height)];
+        self.vertices.extend_from_slice(&[corners[0], corners[2], corners[1], corners[0], corners[3], corners[2]]);
+    }
+
+    fn snap(&self, start: f64, length: f64) -> (f64, f64) {
+        let scale = self.scale_factor;
+        let first = (start * scale).round();
+        let last = ((start + length) * scale).round();
+        let last = if length > 0.0 { last.max(first + 1.0) } else { last };
+        (first / scale, (last - first) / scale)
+    }
+
+    pub fn into_vertices(self) -> Vec<Vertex> {
+        self.vertices
+    }
+}
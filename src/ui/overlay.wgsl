@@ -0,0 +1,29 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+// Проекция камеры в пикселях от левого верхнего угла
+@group(0) @binding(0)
+var<uniform> camera: mat4x4<f32>;
+
+// Вершины уже в пикселях окна, без поворота и масштаба колец
+@vertex
+fn vs_main(
+    vertex: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = vertex.color;
+    out.clip_position = camera * vec4<f32>(vertex.position.xy, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
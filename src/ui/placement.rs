@@ -0,0 +1,37 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+// Положение окна на экране, переживает перезапуск
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Placement {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Placement {
+    //Нет файла или он испорчен - окно встанет туда, куда поставит оконный менеджер
+    pub fn load(path: &Path) -> Option<Placement> {
+        let text = std::fs::read_to_string(path).ok()?;
+        toml::from_str(&text).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string(self).map_err(io::Error::other)?;
+        std::fs::write(path, text)
+    }
+}
+
+//$XDG_STATE_HOME/jarvis/window.toml, иначе ~/.local/state/jarvis/window.toml
+pub fn default_path() -> PathBuf {
+    std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state")))
+        .unwrap_or_default()
+        .join("jarvis")
+        .join("window.toml")
+}
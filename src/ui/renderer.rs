@@ -0,0 +1,454 @@
+use smaa::{SmaaMode, SmaaTarget};
+use wgpu::{BlendComponent, Device, Queue, TextureFormat, TextureView};
+
+use crate::config::{Antialiasing, RenderConfig, RingGeometry};
+use crate::ui::{animator::Frame, bloom::{self, Bloom, BloomChain}, camera::{Camera, Units}, buffers::{self, LayerBuffers, OverlayBuffer}, colors::{self, Theme}, lifecycle::RingStyle, vertex_generator::{Vertex, SEGMENTS}};
+
+// Больше двух слоев одновременно не бывает: уходящий и новый вид во время перехода
+pub const MAX_LAYERS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ring {
+    Full,
+    Dashed,
+}
+
+// Радиусы колец (внешнее свечение от outer к middle, внутреннее от middle к inner)
+// и рисунок штриха: сколько сегментов рисуем и сколько пропускаем.
+// falloff и gradient есть только у колец из шейдера (RingGeometry::Sdf):
+// степень затухания свечения (1 - линейно, как у сетки) и насколько тускнеет цвет по кругу
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RingShape {
+    pub outer: f32,
+    pub middle: f32,
+    pub inner: f32,
+    pub dash: [u32; 2],
+    pub falloff: f32,
+    pub gradient: f32,
+}
+
+impl Default for RingShape {
+    fn default() -> Self {
+        RingShape { outer: 0.45, middle: 0.4, inner: 0.3, dash: [15, 15], falloff: 1.0, gradient: 0.0 }
+    }
+}
+
+// Один слой кольца в кадре
+#[derive(Debug, Clone)]
+pub struct SceneLayer {
+    pub ring: Ring,
+    pub outer_offsets: Vec<f32>,
+    pub inner_offsets: Vec<f32>,
+    pub scale: f32,
+    pub rotation: f32,
+    pub tint: [f32; 4],
+}
+
+// Все, что нужно нарисовать в кадре, без привязки к окну
+#[derive(Debug, Clone)]
+pub struct Scene {
+    pub theme: Theme,
+    pub layers: Vec<SceneLayer>,
+    // Треугольники поверх колец в координатах экрана (контекстное меню)
+    pub overlay: Vec<Vertex>,
+    // Свечение, если оно включено в настройках рендерера
+    pub bloom: Bloom,
+}
+
+impl Scene {
+    //Слои кадра аниматора. audio - смещения сегментов внешнего и внутреннего колец для стиля Audio
+    pub fn from_frame(theme: Theme, frame: &Frame, audio: (&[f32], &[f32])) -> Scene {
+        let layers = frame.layers
+            .iter()
+            .map(|layer| {
+                let pose = &layer.pose;
+                let (ring, outer, inner) = match layer.style {
+                    RingStyle::Solid => (Ring::Full, Vec::new(), Vec::new()),
+                    RingStyle::Dashed => (Ring::Dashed, Vec::new(), Vec::new()),
+                    RingStyle::Audio => (Ring::Full, audio.0.to_vec(), audio.1.to_vec()),
+                };
+                let mut tint = pose.color;
+                tint[3] *= pose.opacity;
+                SceneLayer {
+                    ring,
+                    outer_offsets: shift(outer, pose.radius),
+                    inner_offsets: shift(inner, pose.radius),
+                    scale: pose.scale,
+                    rotation: pose.rotation,
+                    tint,
+                }
+            })
+            .collect();
+        Scene { theme, layers, overlay: Vec::new(), bloom: Bloom::default() }
+    }
+}
+
+//radius сдвигает все сегменты
+fn shift(offsets: Vec<f32>, radius: f32) -> Vec<f32> {
+    if radius == 0.0 {
+        return offsets;
+    }
+    if offsets.is_empty() {
+        return vec![radius; SEGMENTS];
+    }
+    offsets.into_iter().map(|offset| offset + radius).collect()
+}
+
+// Рисует кольца в любую текстуру: в поверхность окна или во внеэкранную цель.
+// Все буферы создаются в new, в кадре только перезаписываются
+pub struct Renderer {
+    render_pipeline: wgpu::RenderPipeline,
+    overlay_pipeline: wgpu::RenderPipeline,
+    overlay: OverlayBuffer,
+    //Вершины меню с цветами, переведенными для sRGB-цели; память переиспользуется между кадрами
+    overlay_vertices: Vec<Vertex>,
+    //Цель в sRGB: цвета перед отрисовкой переводим в линейные
+    linear: bool,
+    index_buffers: Vec<(wgpu::Buffer, u32)>,
+    antialiasing: Antialiasing,
+    geometry: RingGeometry,
+    format: TextureFormat,
+    //Есть только при сглаживании SMAA
+    smaa_target: Option<SmaaTarget>,
+    //Мультисэмпловая цель при MSAA, после прохода сводится в итоговую текстуру
+    msaa_target: Option<TextureView>,
+    //При включенном свечении кольца рисуются в его HDR-текстуру
+    bloom: Option<BloomChain>,
+    //Проекции колец (нормализованные единицы) и меню (логические пиксели),
+    //переписываются при смене размера или масштаба экрана
+    camera: wgpu::Buffer,
+    overlay_camera: wgpu::Buffer,
+    overlay_view: Camera,
+    scale_factor: f64,
+    layers: Vec<LayerBuffers>,
+    shape: RingShape,
+    width: u32,
+    height: u32,
+}
+
+impl Renderer {
+
+    //render.antialiasing должен быть уже проверен на поддержку адаптером (setup::antialiasing)
+    pub fn new(device: &Device, queue: &Queue, format: TextureFormat, width: u32, height: u32, shape: RingShape, render: RenderConfig) -> Renderer {
+        let RenderConfig { antialiasing, geometry, bloom } = render;
+        let scene_format = scene_format(format, &render);
+        //Создаем объект шейдера
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));   
+        let smaa_target = (antialiasing == Antialiasing::Smaa).then(|| SmaaTarget::new(
+            device,
+            queue,
+            width,
+            height,
+            format,
+            SmaaMode::Smaa1X,
+        ));
+        let sample_count = antialiasing.sample_count();
+        let msaa_target = (sample_count > 1).then(|| create_msaa_target(device, scene_format, width, height, sample_count));
+        let bloom = bloom.then(|| BloomChain::new(device, format, width, height));
+        //0 - проекция камеры, 1 - масштаб и поворот слоя, 2 - цвет слоя, 3 - сглаживание концов штрихов,
+        //4 и 5 - форма и смещения сегментов для колец по расстоянию
+        let uniform_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                uniform_entry(0, wgpu::ShaderStages::VERTEX),
+                uniform_entry(1, wgpu::ShaderStages::VERTEX),
+                uniform_entry(2, wgpu::ShaderStages::FRAGMENT),
+                uniform_entry(3, wgpu::ShaderStages::FRAGMENT),
+                uniform_entry(4, wgpu::ShaderStages::VERTEX_FRAGMENT),
+                uniform_entry(5, wgpu::ShaderStages::FRAGMENT),
+            ],
+            label: None,
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&uniform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+    
+        //Создаем графический конвейер
+        let render_pipeline = match geometry {
+            RingGeometry::Mesh => create_pipeline(device, &shader, ("vs_main", "fs_main"), &[Vertex::desc()], &pipeline_layout, scene_format, sample_count),
+            //Квадрат строится в вершинном шейдере, буфер вершин не нужен
+            RingGeometry::Sdf => create_pipeline(device, &shader, ("vs_sdf", "fs_sdf"), &[], &pipeline_layout, scene_format, sample_count),
+        };
+
+        //Меню рисуется поверх колец отдельным конвейером со своей камерой в логических пикселях. Со свечением - уже после
+        //сведения, прямо в итоговую текстуру без мультисэмплинга
+        let overlay_shader = device.create_shader_module(wgpu::include_wgsl!("overlay.wgsl"));
+        let overlay_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[uniform_entry(0, wgpu::ShaderStages::VERTEX)],
+            label: None,
+        });
+        let overlay_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&overlay_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let overlay_pipeline = create_pipeline(device, &overlay_shader, ("vs_main", "fs_main"), &[Vertex::desc()], &overlay_layout, format, if bloom.is_some() { 1 } else { sample_count });
+        let index_buffers = buffers::create_index(shape.dash, device);
+
+        let camera = buffers::create_camera(&Camera::new((width, height), 1.0, Units::Normalized), device);
+        let overlay_camera = buffers::create_camera(&Camera::new((width, height), 1.0, Units::Pixels), device);
+        let overlay = OverlayBuffer::new(&overlay_camera, &overlay_bind_group_layout, device);
+        let layers = (0..MAX_LAYERS)
+            .map(|_| LayerBuffers::new(&camera, &uniform_bind_group_layout, device))
+            .collect();
+
+        Renderer {
+            render_pipeline,
+            overlay_pipeline,
+            overlay,
+            overlay_vertices: Vec::new(),
+            linear: format.is_srgb(),
+            index_buffers,
+            antialiasing,
+            geometry,
+            format,
+            smaa_target,
+            msaa_target,
+            bloom,
+            camera,
+            overlay_camera,
+            overlay_view: Camera::new((width, height), 1.0, Units::Pixels),
+            scale_factor: 1.0,
+            layers,
+            shape,
+            width,
+            height,
+        }
+    }
+
+    //Новые радиусы подхватываются вершинами в следующем кадре, индексы пересоздаем только при смене штриха
+    pub fn set_shape(&mut self, device: &Device, shape: RingShape) {
+        if shape.dash != self.shape.dash {
+            self.index_buffers = buffers::create_index(shape.dash, device);
+        }
+        self.shape = shape;
+    }
+
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        if let Some(smaa_target) = &mut self.smaa_target {
+            smaa_target.resize(device, width, height);
+        }
+        if self.msaa_target.is_some() {
+            let format = if self.bloom.is_some() { bloom::HDR_FORMAT } else { self.format };
+            self.msaa_target = Some(create_msaa_target(device, format, width, height, self.antialiasing.sample_count()));
+        }
+        if let Some(bloom) = &mut self.bloom {
+            bloom.resize(device, width, height);
+        }
+    }
+
+    //Сколько физических пикселей в логическом, меню и графики задаются в логических
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+
+    pub fn render(&mut self, device: &Device, queue: &Queue, view: &TextureView, scene: &Scene) {
+        let size = (self.width, self.height);
+        let overlay_view = Camera::new(size, self.scale_factor, Units::Pixels);
+        if overlay_view != self.overlay_view {
+            buffers::write_camera(&self.camera, &Camera::new(size, self.scale_factor, Units::Normalized), queue);
+            buffers::write_camera(&self.overlay_camera, &overlay_view, queue);
+            self.overlay_view = overlay_view;
+        }
+
+        let theme = if self.linear { scene.theme.to_linear() } else { scene.theme };
+        let count = scene.layers.len().min(MAX_LAYERS);
+        for (layer, buffers) in scene.layers.iter().zip(&mut self.layers) {
+            match self.geometry {
+                RingGeometry::Mesh => buffers.write_geometry(&theme, &self.shape, &layer.outer_offsets, &layer.inner_offsets, queue),
+                RingGeometry::Sdf => buffers.write_ring(&theme, &self.shape, layer.ring == Ring::Dashed, &layer.outer_offsets, &layer.inner_offsets, queue),
+            }
+            buffers.write_transform(layer.scale, layer.rotation, queue);
+            buffers.write_tint(layer.tint, queue);
+            let analytic = self.antialiasing == Antialiasing::Analytic;
+            buffers.write_edge(analytic, layer.ring == Ring::Dashed, self.shape.dash, queue);
+        }
+        if self.linear {
+            self.overlay_vertices.clear();
+            self.overlay_vertices.extend(scene.overlay.iter().map(|vertex| vertex.map_color(colors::to_linear)));
+            self.overlay.write(&self.overlay_vertices, queue);
+        }
+        else {
+            self.overlay.write(&scene.overlay, queue);
+        }
+
+        //Куда в итоге попадает кадр: во вход SMAA или сразу в view. Кольца рисуются туда же
+        //или в HDR-текстуру свечения; при MSAA - в мультисэмпловую текстуру, которая туда сводится
+        let smaa_frame = self.smaa_target.as_mut().map(|smaa_target| smaa_target.start_frame(device, queue, view));
+        let output = smaa_frame.as_deref().unwrap_or(view);
+        let rings = self.bloom.as_ref().map_or(output, |bloom| bloom.scene());
+        let (attachment, resolve_target) = match &self.msaa_target {
+            Some(msaa_target) => (msaa_target, Some(rings)),
+            None => (rings, None),
+        };
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {label: None});
+        {
+            //Кольца смешиваются с фоном с умножением на альфу, так же очищаем и фон:
+            //у прозрачного фона цвет нужен только для затухания свечения
+            let background = theme.background;
+            let alpha = background[3] as f64;
+            let mut rpass =
+                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: None,
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: attachment,
+                        resolve_target,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color {
+                                r: background[0] as f64 * alpha,
+                                g: background[1] as f64 * alpha,
+                                b: background[2] as f64 * alpha,
+                                a: alpha,
+                            }),
+                            //Сэмплы после сведения не нужны
+                            store: if resolve_target.is_some() { wgpu::StoreOp::Discard } else { wgpu::StoreOp::Store },
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+
+            rpass.set_pipeline(&self.render_pipeline);
+
+            for (layer, buffers) in scene.layers[..count].iter().zip(&self.layers) {
+                rpass.set_bind_group(0, buffers.bind_group(), &[]);
+                if self.geometry == RingGeometry::Sdf {
+                    rpass.draw(0..6, 0..1);
+                    continue;
+                }
+                let (index_buffer, index_count) = match layer.ring {
+                    Ring::Full => &self.index_buffers[0],
+                    Ring::Dashed => &self.index_buffers[1],
+                };
+                for vertex_buffer in buffers.vertex() {
+                    rpass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    rpass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    rpass.draw_indexed(0..*index_count,0, 0..1);
+                }
+            }
+
+            if self.bloom.is_none() {
+                draw_overlay(&mut rpass, &self.overlay_pipeline, &self.overlay);
+            }
+        }
+        if let Some(bloom) = &mut self.bloom {
+            let (pipeline, overlay) = (&self.overlay_pipeline, &self.overlay);
+            bloom.run(&mut encoder, queue, &scene.bloom, output, |rpass| draw_overlay(rpass, pipeline, overlay));
+        }
+        queue.submit(Some(encoder.finish()));
+        if let Some(smaa_frame) = smaa_frame {
+            smaa_frame.resolve();
+        }
+    }
+}
+
+fn draw_overlay(rpass: &mut wgpu::RenderPass<'_>, pipeline: &wgpu::RenderPipeline, overlay: &OverlayBuffer) {
+    if !overlay.is_empty() {
+        rpass.set_pipeline(pipeline);
+        rpass.set_bind_group(0, overlay.bind_group(), &[]);
+        rpass.set_vertex_buffer(0, overlay.vertex().slice(..));
+        rpass.draw(0..overlay.len(), 0..1);
+    }
+}
+
+//В каком формате рисуются кольца при выводе в output: со свечением - в HDR.
+//По нему же проверяется поддержка MSAA
+pub fn scene_format(output: TextureFormat, render: &RenderConfig) -> TextureFormat {
+    if render.bloom { bloom::HDR_FORMAT } else { output }
+}
+
+fn create_msaa_target(device: &Device, format: TextureFormat, width: u32, height: u32, sample_count: u32) -> TextureView {
+    buffers::count_allocation();
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+fn uniform_entry(binding: u32, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+//Кольца и меню рисуются одинаково: треугольники с премножением на альфу, без глубины
+#[allow(clippy::too_many_arguments)]
+fn create_pipeline(
+    device: &Device,
+    shader: &wgpu::ShaderModule,
+    (vertex_entry, fragment_entry): (&str, &str),
+    buffers: &[wgpu::VertexBufferLayout],
+    layout: &wgpu::PipelineLayout,
+    format: TextureFormat,
+    sample_count: u32,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: None,
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: vertex_entry,
+            buffers,
+            compilation_options:  wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: fragment_entry,
+            compilation_options:  wgpu::PipelineCompilationOptions::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState {
+                    color: BlendComponent {
+                        src_factor: wgpu::BlendFactor::SrcAlpha,
+                        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                        operation: wgpu::BlendOperation::Add
+                    },
+                    alpha: BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                        operation: wgpu::BlendOperation::Add
+                    }
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList, 
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw, 
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None, 
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            mask: !0, 
+            alpha_to_coverage_enabled: false, 
+        },
+        multiview: None,
+        cache: None, 
+    })
+}
@@ -1,61 +1,257 @@
+use std::fmt;
+
+use winit::error::{EventLoopError, OsError};
 use winit::window::Window;
 
+use crate::config::{Antialiasing, PresentMode, WindowConfig};
+
 pub struct Preload<'a> {
     pub surface: wgpu::Surface<'a>,
+    //Нужен, чтобы проверять поддержку форматов, например число сэмплов для MSAA
+    pub adapter: wgpu::Adapter,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub config: wgpu::SurfaceConfiguration,
     pub size: winit::dpi::PhysicalSize<u32>,
+    //Что умеет поверхность: режим показа можно сменить на лету, не пересоздавая ее
+    pub present_modes: Vec<wgpu::PresentMode>,
 }
 
-pub async fn start(window: &Window) -> Preload {
-            //Устанавливаем размер окна
-            let size = window.inner_size();
-    
-            let instance = wgpu::Instance::default();
-            //Создаем поверхность
-            let surface = instance.create_surface(window).unwrap();
-            //Запрашиваем адаптер для работы с графикой
-            let adapter = instance
-                .request_adapter(&wgpu::RequestAdapterOptions {
-                    power_preference: wgpu::PowerPreference::HighPerformance,
-                    force_fallback_adapter: false,
-                    compatible_surface: Some(&surface),
-                })
-                .await.unwrap();
-            //Создаем устройство из адаптера
-            let (device, queue) = adapter
-                .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: None,
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default()
-                        .using_resolution(adapter.limits()),
-                    memory_hints: wgpu::MemoryHints::Performance,
-                },
-                None,
-            )
-            .await.unwrap();
-    
-            let swapchain_capabilities = surface.get_capabilities(&adapter);
-   
-            //Создаем конфиг для поверхности
-            let config = wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: wgpu::TextureFormat::Bgra8Unorm,
-                width: size.width,
-                height: size.height,
-                present_mode: swapchain_capabilities.present_modes[0],
-                alpha_mode: swapchain_capabilities.alpha_modes[0],
-                view_formats: vec![],
-                desired_maximum_frame_latency: 3,
+// На каком этапе запуска не получилось: цикл событий, окно, поверхность, адаптер или устройство
+#[derive(Debug)]
+pub enum SetupError {
+    EventLoop(EventLoopError),
+    Window(OsError),
+    Surface(wgpu::CreateSurfaceError),
+    // Ни одного адаптера, даже программного
+    NoAdapter,
+    // Адаптеры есть, но ни один не умеет рисовать в это окно
+    UnsupportedSurface { adapter: String },
+    // Адаптеры нашлись, но устройство не создалось ни на одном (обычно не хватает лимитов)
+    Device { attempts: Vec<(String, wgpu::RequestDeviceError)> },
+}
+
+impl fmt::Display for SetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupError::EventLoop(err) => write!(f, "can't create the event loop: {err}"),
+            SetupError::Window(err) => write!(f, "can't open the window: {err}"),
+            SetupError::Surface(err) => write!(f, "can't create a drawing surface for the window: {err}"),
+            SetupError::NoAdapter => write!(
+                f,
+                "no graphics adapter found (tried high-performance, low-power and software); \
+                 check that a Vulkan, Metal, DirectX 12 or OpenGL driver is installed"
+            ),
+            SetupError::UnsupportedSurface { adapter } => write!(
+                f,
+                "graphics adapter `{adapter}` can't present to this window; \
+                 try another display server or update the graphics driver"
+            ),
+            SetupError::Device { attempts } => {
+                write!(f, "can't create a graphics device:")?;
+                for (adapter, err) in attempts {
+                    write!(f, "\n  {adapter}: {err}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SetupError {}
+
+// Порядок поиска адаптера: мощная видеокарта, встроенная, программная отрисовка
+const FALLBACK_CHAIN: [(wgpu::PowerPreference, bool); 3] = [
+    (wgpu::PowerPreference::HighPerformance, false),
+    (wgpu::PowerPreference::LowPower, false),
+    (wgpu::PowerPreference::None, true),
+];
+
+//overlay - окну нужен прозрачный фон, альфа кадра должна смешиваться с рабочим столом
+pub async fn start<'a>(window: &'a Window, settings: &WindowConfig) -> Result<Preload<'a>, SetupError> {
+    //Устанавливаем размер окна
+    let size = window.inner_size();
+
+    let instance = wgpu::Instance::default();
+    //Создаем поверхность
+    let surface = instance.create_surface(window).map_err(SetupError::Surface)?;
+    //Запрашиваем адаптер и создаем устройство, идя по цепочке адаптеров
+    let (adapter, device, queue) = match request(&instance, Some(&surface), &FALLBACK_CHAIN).await {
+        Err(SetupError::NoAdapter) => {
+            //Различаем "адаптеров нет вовсе" и "есть, но не для этого окна"
+            return match instance.request_adapter(&wgpu::RequestAdapterOptions::default()).await {
+                Some(adapter) => Err(SetupError::UnsupportedSurface { adapter: adapter.get_info().name }),
+                None => Err(SetupError::NoAdapter),
             };
+        }
+        result => result?,
+    };
+
+    let info = adapter.get_info();
+    println!("Adapter: {} ({:?}, {:?})", info.name, info.backend, info.device_type);
+
+    let swapchain_capabilities = surface.get_capabilities(&adapter);
+    if swapchain_capabilities.formats.is_empty() {
+        return Err(SetupError::UnsupportedSurface { adapter: info.name });
+    }
+
+    //Создаем конфиг для поверхности
+    let config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format: surface_format(&swapchain_capabilities.formats),
+        width: size.width,
+        height: size.height,
+        present_mode: present_mode(&swapchain_capabilities.present_modes, settings.present_mode),
+        alpha_mode: alpha_mode(&swapchain_capabilities.alpha_modes, settings.overlay),
+        view_formats: vec![],
+        desired_maximum_frame_latency: 3,
+    };
+
+    Ok(Preload {
+        surface,
+        adapter,
+        device,
+        queue,
+        config,
+        size,
+        present_modes: swapchain_capabilities.present_modes,
+    })
+}
+
+//Первый адаптер из цепочки, на котором создалось устройство. Один и тот же адаптер дважды не пробуем
+async fn request(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface<'_>>,
+    chain: &[(wgpu::PowerPreference, bool)],
+) -> Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue), SetupError> {
+    let mut attempts: Vec<(String, wgpu::RequestDeviceError)> = Vec::new();
+    let mut tried = Vec::new();
+    for (power_preference, force_fallback_adapter) in chain {
+        let options = wgpu::RequestAdapterOptions {
+            power_preference: *power_preference,
+            force_fallback_adapter: *force_fallback_adapter,
+            compatible_surface: surface,
+        };
+        let Some(adapter) = instance.request_adapter(&options).await else { continue };
+        let info = adapter.get_info();
+        if tried.contains(&info) {
+            continue;
+        }
+        match request_device(&adapter).await {
+            Ok((device, queue)) => return Ok((adapter, device, queue)),
+            Err(err) => {
+                eprintln!("Can't use adapter {}: {err}, trying the next one", info.name);
+                attempts.push((info.name.clone(), err));
+            }
+        }
+        tried.push(info);
+    }
+    if attempts.is_empty() { Err(SetupError::NoAdapter) } else { Err(SetupError::Device { attempts }) }
+}
 
-            Preload {
-                surface,
-                device,
-                queue,
-                config,
-                size
+//Берем sRGB-формат, чтобы смешивание и градиенты считались в линейном цвете, а на экран шли
+//правильные значения. Без sRGB - первый поддерживаемый, цвета тогда смешиваются как есть
+pub fn surface_format(supported: &[wgpu::TextureFormat]) -> wgpu::TextureFormat {
+    use wgpu::TextureFormat::*;
+
+    [Bgra8UnormSrgb, Rgba8UnormSrgb]
+        .into_iter()
+        .find(|format| supported.contains(format))
+        .or_else(|| supported.iter().copied().find(|format| format.is_srgb()))
+        .unwrap_or(supported[0])
+}
+
+//Режим из настроек, если поверхность его умеет, иначе ближайший: без разрывов, затем vsync.
+//Fifo поддерживается везде, первый из списка - на случай совсем странного драйвера
+pub fn present_mode(supported: &[wgpu::PresentMode], wanted: PresentMode) -> wgpu::PresentMode {
+    use wgpu::PresentMode::*;
+
+    let preferred: &[wgpu::PresentMode] = match wanted {
+        PresentMode::Vsync => &[Fifo],
+        PresentMode::Mailbox => &[Mailbox, Fifo],
+        PresentMode::Immediate => &[Immediate, Mailbox, Fifo],
+    };
+    let mode = preferred.iter().copied().find(|mode| supported.contains(mode)).unwrap_or(supported[0]);
+    if mode != preferred[0] {
+        eprintln!("Present mode {wanted:?} isn't supported (available {supported:?}), using {mode:?}");
+    }
+    mode
+}
+
+//Кадр рисуется с умноженной на альфу прозрачностью, поэтому для прозрачного окна ищем PreMultiplied.
+//Непрозрачному окну альфа не нужна вовсе
+pub fn alpha_mode(supported: &[wgpu::CompositeAlphaMode], transparent: bool) -> wgpu::CompositeAlphaMode {
+    use wgpu::CompositeAlphaMode::*;
+
+    let preferred: &[wgpu::CompositeAlphaMode] = if transparent {
+        &[PreMultiplied, Inherit, PostMultiplied]
+    } else {
+        &[Opaque]
+    };
+    match preferred.iter().find(|mode| supported.contains(mode)) {
+        Some(mode) => *mode,
+        None => {
+            if transparent {
+                eprintln!("Surface doesn't support transparency (alpha modes {supported:?}), the background will be opaque");
             }
-}
\ No newline at end of file
+            supported[0]
+        }
+    }
+}
+
+//Устройство без поверхности, для внеэкранной отрисовки. Адаптер ищем по той же цепочке,
+//software - сразу программный, чтобы картинка не зависела от видеокарты
+pub async fn headless(software: bool) -> Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue), SetupError> {
+    headless_on(&wgpu::Instance::default(), software).await
+}
+
+//То же на своем экземпляре wgpu, например чтобы потом спросить у него отчет о ресурсах
+pub async fn headless_on(instance: &wgpu::Instance, software: bool) -> Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue), SetupError> {
+    let chain = if software { &FALLBACK_CHAIN[2..] } else { &FALLBACK_CHAIN[..] };
+    request(instance, None, chain).await
+}
+
+//Сглаживание из настроек, если адаптер умеет столько сэмплов для формата цели
+pub fn antialiasing(adapter: &wgpu::Adapter, device: &wgpu::Device, format: wgpu::TextureFormat, wanted: Antialiasing) -> Antialiasing {
+    //Без этой возможности устройства доступны только гарантированные для формата режимы
+    let flags = if device.features().contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+        adapter.get_texture_format_features(format).flags
+    } else {
+        format.guaranteed_format_features(device.features()).flags
+    };
+    let mode = fallback_antialiasing(wanted, |count| flags.sample_count_supported(count));
+    if mode != wanted {
+        eprintln!("Anti-aliasing {wanted:?} isn't supported for {format:?}, using {mode:?}");
+    }
+    mode
+}
+
+//MSAA 8x откатывается на 4x, а без мультисэмплинга вовсе - на SMAA. Остальные режимы работают везде
+pub fn fallback_antialiasing(wanted: Antialiasing, supported: impl Fn(u32) -> bool) -> Antialiasing {
+    let preferred: &[Antialiasing] = match wanted {
+        Antialiasing::Msaa8 => &[Antialiasing::Msaa8, Antialiasing::Msaa4],
+        Antialiasing::Msaa4 => &[Antialiasing::Msaa4],
+        mode => return mode,
+    };
+    preferred
+        .iter()
+        .copied()
+        .find(|mode| supported(mode.sample_count()))
+        .unwrap_or(Antialiasing::Smaa)
+}
+
+async fn request_device(adapter: &wgpu::Adapter) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+    adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                //Чтобы узнать, сколько сэмплов MSAA адаптер на самом деле умеет
+                required_features: adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+                required_limits: wgpu::Limits::default()
+                    .using_resolution(adapter.limits()),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        )
+        .await
+}
@@ -7,29 +7,189 @@ struct VertexInput {
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(1) local: vec2<f32>,
+    // 0 на контуре кольца, 1 на стыке с соседней полосой
+    @location(2) outline: f32,
 };
-struct TransformUniform {
-    scale: mat4x4<f32>,
-    rotation: mat4x4<f32>,
+// x - включено ли сглаживание краев, y и z - сколько сегментов рисуем и сколько пропускаем, w - штрих ли
+struct EdgeUniform {
+    enabled: f32,
+    on: f32,
+    off: f32,
+    dashed: f32,
 };
 
+// Проекция камеры в нормализованных единицах и положение слоя: масштаб и поворот
 @group(0) @binding(0)
-var<uniform> aspect_ratio: vec4<f32>;
+var<uniform> camera: mat4x4<f32>;
 @group(0) @binding(1)
-var<uniform> transform: TransformUniform;
+var<uniform> model: mat4x4<f32>;
+@group(0) @binding(2)
+var<uniform> tint: vec4<f32>;
+@group(0) @binding(3)
+var<uniform> edge: EdgeUniform;
 
 
 @vertex
 fn vs_main(
-    model: VertexInput,
+    vertex: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.color = model.color;
-    out.clip_position = transform.scale * transform.rotation * vec4<f32>(model.position, 1.0) * aspect_ratio;
+    out.color = vertex.color;
+    out.local = vertex.position.xy;
+    out.outline = vertex.position.z;
+    out.clip_position = camera * model * vec4<f32>(vertex.position.xy, 0.0, 1.0);
     return out;
 }
 
+// Сегмент k кольца занимает углы [2k-1, 2k+1], нулевой - [0, 1], последний, 180-й - [359, 360].
+// Нулевой рисуется всегда, остальные по рисунку штриха
+fn drawn(segment: f32, on: f32, off: f32) -> bool {
+    return segment == 0.0 || segment % (on + off) < on;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.color);
-}
\ No newline at end of file
+    //Размер пикселя в координатах кольца, производные берем до любых ветвлений
+    let pixel = max(fwidth(in.local).x, fwidth(in.local).y);
+    let outline_pixel = max(fwidth(in.outline), 1.0e-6);
+    var color = in.color * tint;
+    if edge.enabled > 0.5 {
+        //Пиксель, центр которого лежит на контуре, покрыт наполовину, отсюда 0.5
+        color.a *= clamp(0.5 + in.outline / outline_pixel, 0.0, 1.0);
+    }
+    if edge.enabled > 0.5 && edge.dashed > 0.5 {
+        let radius = length(in.local);
+        var angle = degrees(atan2(in.local.y, in.local.x));
+        if angle < 0.0 {
+            angle += 360.0;
+        }
+        let segment = min(floor((angle + 1.0) / 2.0), 180.0);
+        let previous = select(segment - 1.0, 180.0, segment == 0.0);
+        let next = select(segment + 1.0, 0.0, segment == 180.0);
+        //Расстояние в пикселях до ближайшего конца штриха, у которого соседний сегмент не рисуется
+        var distance = 1.0e6;
+        if !drawn(previous, edge.on, edge.off) {
+            distance = min(distance, angle - max(2.0 * segment - 1.0, 0.0));
+        }
+        if !drawn(next, edge.on, edge.off) {
+            distance = min(distance, min(2.0 * segment + 1.0, 360.0) - angle);
+        }
+        color.a *= clamp(0.5 + radians(distance) * radius / pixel, 0.0, 1.0);
+    }
+    return color;
+}
+
+// Кольцо по расстоянию до краев (RingGeometry::Sdf): один квадрат на кольцо, форма, штрихи и свечение
+// считаются для каждого пикселя, поэтому края остаются ровными при любом масштабе
+struct RingUniform {
+    // outer, middle, inner, степень затухания свечения
+    radii: vec4<f32>,
+    // рисуем, пропускаем, штрих ли, насколько тускнеет цвет по кругу
+    dash: vec4<f32>,
+    accent: vec4<f32>,
+    background: vec4<f32>,
+    // x - половина стороны квадрата
+    extent: vec4<f32>,
+};
+
+@group(0) @binding(4)
+var<uniform> ring: RingUniform;
+// Смещения 180 сегментов внешнего кольца, затем внутреннего, по четыре в vec4
+@group(0) @binding(5)
+var<uniform> offsets: array<vec4<f32>, 90>;
+
+struct SdfOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) local: vec2<f32>,
+};
+
+@vertex
+fn vs_sdf(@builtin(vertex_index) index: u32) -> SdfOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    var out: SdfOutput;
+    out.local = corners[index] * ring.extent.x;
+    out.clip_position = camera * model * vec4<f32>(out.local, 0.0, 1.0);
+    return out;
+}
+
+fn segment_offset(first: u32, segment: u32) -> f32 {
+    let index = first + segment;
+    return offsets[index / 4u][index % 4u];
+}
+
+// Смещение сегмента стоит на его середине (угол 2k+1), между серединами - линейно, как у сетки
+fn radial_offset(first: u32, angle: f32) -> f32 {
+    let position = (angle - 1.0) / 2.0;
+    let before = floor(position);
+    let segment = u32((i32(before) + 180) % 180);
+    return mix(segment_offset(first, segment), segment_offset(first, (segment + 1u) % 180u), position - before);
+}
+
+// Цвет полосы свечения с покрытием по краям; glow - 1 у яркого края, 0 у края цвета фона
+fn band(radius: f32, low: f32, high: f32, glow: f32, accent: vec4<f32>, pixel: f32) -> vec4<f32> {
+    let distance = max(low - radius, radius - high);
+    let coverage = clamp(0.5 - distance / pixel, 0.0, 1.0);
+    let color = mix(ring.background, accent, pow(clamp(glow, 0.0, 1.0), ring.radii.w));
+    return vec4<f32>(color.rgb, color.a * coverage);
+}
+
+// Расстояние в градусах до края штриха: внутри рисуемого сегмента положительное,
+// в пропуске - отрицательное. Края считаются только там, где рисуемый сегмент соседствует с пропуском
+fn dash_distance(angle: f32) -> f32 {
+    let segment = min(floor((angle + 1.0) / 2.0), 180.0);
+    let previous = select(segment - 1.0, 180.0, segment == 0.0);
+    let next = select(segment + 1.0, 0.0, segment == 180.0);
+    let inside = drawn(segment, ring.dash.x, ring.dash.y);
+    var distance = 1.0e6;
+    if drawn(previous, ring.dash.x, ring.dash.y) != inside {
+        distance = min(distance, angle - max(2.0 * segment - 1.0, 0.0));
+    }
+    if drawn(next, ring.dash.x, ring.dash.y) != inside {
+        distance = min(distance, min(2.0 * segment + 1.0, 360.0) - angle);
+    }
+    return select(-distance, distance, inside);
+}
+
+@fragment
+fn fs_sdf(in: SdfOutput) -> @location(0) vec4<f32> {
+    //Размер пикселя в координатах кольца, производные берем до любых ветвлений
+    let pixel = max(fwidth(in.local).x, fwidth(in.local).y);
+    let radius = length(in.local);
+    var angle = degrees(atan2(in.local.y, in.local.x));
+    if angle < 0.0 {
+        angle += 360.0;
+    }
+
+    //Угловой градиент: у нуля градусов цвет полный, напротив тускнеет на gradient
+    let dim = 1.0 - ring.dash.w * (0.5 - 0.5 * cos(radians(angle)));
+    let accent = vec4<f32>(ring.accent.rgb * dim, ring.accent.a);
+
+    let outer_shift = radial_offset(0u, angle);
+    let inner_shift = radial_offset(180u, angle);
+    let middle = ring.radii.y;
+    let outer_low = middle + outer_shift;
+    let outer_high = ring.radii.x + outer_shift;
+    let inner_low = ring.radii.z + inner_shift;
+    let inner_high = middle + inner_shift;
+    let outer = band(radius, outer_low, outer_high, (outer_high - radius) / (outer_high - outer_low), accent, pixel);
+    let inner = band(radius, inner_low, inner_high, (radius - inner_low) / (inner_high - inner_low), accent, pixel);
+
+    //Внутреннее кольцо у сетки рисуется вторым, поверх внешнего
+    let alpha = inner.a + outer.a * (1.0 - inner.a);
+    if alpha <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+    var color = vec4<f32>((inner.rgb * inner.a + outer.rgb * outer.a * (1.0 - inner.a)) / alpha, alpha) * tint;
+    if ring.dash.z > 0.5 {
+        color.a *= clamp(0.5 + radians(dash_distance(angle)) * radius / pixel, 0.0, 1.0);
+    }
+    return color;
+}
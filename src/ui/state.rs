@@ -1,253 +1,436 @@
+use std::path::Path;
 
-use std::f32::consts::PI;
+use wgpu::SurfaceError;
+use winit::{dpi::{PhysicalPosition, PhysicalSize}, event::MouseButton, window::Window};
 
-use smaa::{SmaaMode, SmaaTarget};
-use wgpu::{BindGroupLayout, BlendComponent};
-use winit::window::Window;
+use crate::audio::{input::{AudioInput, InputSource, WINDOW_SIZE}, level::{smoothing_step, LevelMeter}, spectrum::Spectrum, waveform::Waveform};
+use crate::config::{Config, RenderConfig};
+use crate::ui::{action::Action, animator::Animator, bloom::Bloom, clock::SystemClock, colors::Theme, debug::FrameGraph, headless::{self, HeadlessError}, hit_test, lifecycle::{AssistantState, VisualMode}, menu::{Menu, MenuItem}, renderer::{self, Renderer, Scene}, setup::{self, Preload, SetupError}, surface::{self, Recovery, Visibility}, vertex_generator::SEGMENTS};
 
-use crate::ui::{buffers, setup::{self, Preload}, vertex_generator::Vertex};
+// Постоянные времени огибающей громкости, сек
+const LEVEL_ATTACK: f32 = 0.025;
+const LEVEL_RELEASE: f32 = 0.2;
+// Максимальное радиальное смещение сегмента в режиме спектра
+const SPECTRUM_DEPTH: f32 = 0.15;
+// Постоянная времени сглаживания полос (сек) и скорость спада пиков (в секунду)
+const SPECTRUM_SMOOTHING: f32 = 0.033;
+const SPECTRUM_PEAK_DECAY: f32 = 0.6;
+// Сколько сэмплов осциллограммы укладываем на окружность и насколько сильно они смещают кольцо
+const WAVEFORM_SPAN: usize = 1024;
+const WAVEFORM_DEPTH: f32 = 0.12;
+// Насколько ярче кольцо под курсором и как быстро меняется подсветка, сек
+const HOVER_BRIGHTNESS: f32 = 0.3;
+const HOVER_SMOOTHING: f32 = 0.08;
 
 pub struct State<'a> {
     window: &'a Window,
     hardware: Preload<'a>,
-    render_pipeline: wgpu::RenderPipeline,
-    index_buffers: Vec<(wgpu::Buffer, u32)>,
-    uniform_bind_group_layout: BindGroupLayout,
-    smaa_target: SmaaTarget,
-    rotation: f32,
-    scale: f32,
-    is_record: bool,
-    counter:i16,
-    direction: i16
+    renderer: Renderer,
+    source: InputSource,
+    audio: Option<AudioInput>,
+    level: LevelMeter,
+    spectrum: Option<Spectrum>,
+    spectrum_bins: usize,
+    waveform: Waveform,
+    mode: VisualMode,
+    theme: Theme,
+    theme_name: String,
+    transparent: bool,
+    config: Config,
+    external_level: f32,
+    animator: Animator,
+    //Курсор в физических пикселях окна
+    cursor: Option<(f64, f64)>,
+    hover: f32,
+    //Сколько физических пикселей в логическом у монитора, где сейчас окно
+    scale_factor: f64,
+    //Свечение плавно переходит к настройкам нового состояния вместе со сменой вида
+    bloom: Bloom,
+    menu: Option<Menu>,
+    frames: FrameGraph,
+    debug_overlay: bool,
+    visibility: Visibility,
+    //Последний нарисованный кадр, по нему проверяем попадание мышью
+    scene: Option<Scene>,
 }
 
 impl<'a> State<'a> {
 
-    pub async fn new(window: &'a Window) -> State<'a> {
+    pub async fn new(window: &'a Window, source: InputSource, spectrum_bins: usize, config: Config) -> Result<State<'a>, SetupError> {
         // Настройка поверхности и устройства
-        let hardware = setup::start(window).await;
+        let hardware = setup::start(window, &config.window).await?;
         
-        //Создаем объект шейдера
-        let shader = hardware.device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));   
-        let smaa_target = SmaaTarget::new(
-            &hardware.device,
-            &hardware.queue,
-            hardware.config.width,
-            hardware.config.height,
-            hardware.config.format,
-            SmaaMode::Smaa1X,
-        );
-        let uniform_bind_group_layout = hardware.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::VERTEX,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }
-            ],
-            label: None,
-        });
-
-        let pipeline_layout = hardware.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: None,
-            bind_group_layouts: &[&uniform_bind_group_layout],
-            push_constant_ranges: &[],
-        });
-    
-        //Создаем графический конвейер
-        let render_pipeline = hardware.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: None,
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[
-                    Vertex::desc(),
-                ],
-                compilation_options:  wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                compilation_options:  wgpu::PipelineCompilationOptions::default(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: hardware.config.format,
-                    blend: Some(wgpu::BlendState {
-                        color: BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add
-                        },
-                        alpha: BlendComponent {
-                            src_factor: wgpu::BlendFactor::One,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add
-                        }
-                    }),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList, 
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw, 
-                cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None, 
-            multisample: wgpu::MultisampleState {
-                count: 1, 
-                mask: !0, 
-                alpha_to_coverage_enabled: false, 
-            },
-            multiview: None,
-            cache: None, 
-        });
-        let index_buffers = buffers::create_index(&hardware.device);
-
-        Self {
+        let scale_factor = window.scale_factor();
+        let renderer = create_renderer(&hardware, &config, scale_factor);
+        let visibility = Visibility::new(hardware.config.width, hardware.config.height);
+        let mut animator = Animator::new(Box::new(SystemClock::new()), AssistantState::Idle);
+        animator.set_scale(config.ring.scale);
+        animator.set_speed(config.animation.speed);
+        animator.set_crossfade(config.animation.crossfade);
+        let bloom = config.bloom.state(AssistantState::Idle);
+
+        Ok(Self {
             window,
             hardware,
-            render_pipeline,
-            index_buffers,
-            uniform_bind_group_layout,
-            smaa_target,
-            scale: 1.4,
-            rotation: 0.0,
-            is_record: false,
-            counter: 0,
-            direction: 1,
-        }
+            renderer,
+            source,
+            audio: None,
+            level: LevelMeter::new(LEVEL_ATTACK, LEVEL_RELEASE),
+            spectrum: None,
+            spectrum_bins,
+            waveform: Waveform::new(WAVEFORM_SPAN, SEGMENTS),
+            mode: VisualMode::Dashed,
+            theme: config.initial_theme(),
+            theme_name: config.theme.name.clone(),
+            transparent: config.window.overlay,
+            config,
+            external_level: 0.0,
+            animator,
+            cursor: None,
+            hover: 0.0,
+            scale_factor,
+            bloom,
+            menu: None,
+            frames: FrameGraph::default(),
+            debug_overlay: false,
+            visibility,
+            scene: None,
+        })
     }
 
     pub fn window(&self) -> &Window {
-        &self.window
+        self.window
     }
     //Метод - при изменении размера окна нужно переконфигурировать размер поверхности
+    //Свернутое окно получает размер 0x0: такую поверхность не настраиваем, а просто не рисуем до восстановления
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         self.hardware.size = new_size;
+        if !self.visibility.resize(new_size.width, new_size.height) {
+            return;
+        }
         self.hardware.config.width = new_size.width;
         self.hardware.config.height = new_size.height;
         self.hardware.surface.configure(&self.hardware.device, &self.hardware.config);
 
-        self.smaa_target.resize(&self.hardware.device, new_size.width, new_size.height);
+        self.renderer.resize(&self.hardware.device, new_size.width, new_size.height);
     }
-    pub fn start_record(&mut self) {
-        self.is_record = !self.is_record;
-        self.counter = 0;
-        self.direction = 1;
-        self.rotation = 0.0;
-        self.scale = 1.4;
-        
-        if self.is_record {
+    //Окно перешло на монитор с другой плотностью пикселей. Новый физический размер придет следом через resize
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+        self.renderer.set_scale_factor(scale_factor);
+    }
+    pub fn set_occluded(&mut self, occluded: bool) {
+        self.visibility.set_occluded(occluded);
+    }
+    //Пока окно свернуто или закрыто, цикл событий может спать
+    pub fn is_paused(&self) -> bool {
+        self.visibility.is_paused()
+    }
+    pub fn assistant_state(&self) -> AssistantState {
+        self.animator.state()
+    }
+    //Переход в новое состояние, запрещенные переходы и запись без источника звука игнорируются
+    pub fn set_assistant_state(&mut self, next: AssistantState) -> bool {
+        let previous = self.animator.state();
+        if next == AssistantState::Listening && !self.source.is_available() {
+            println!("Recording is disabled: built without the `microphone` feature, use --wav <file>");
+            return false;
+        }
+        if !self.animator.transition(next) {
+            println!("Ignored transition {previous:?} -> {next:?}");
+            return false;
+        }
+
+        if next == AssistantState::Listening {
+            if let Err(err) = self.start_audio() {
+                eprintln!("Can't start recording: {err}");
+                return self.set_assistant_state(AssistantState::Error);
+            }
             println!("Start recording");
-            //stream play
         }
-        else {
+        else if self.audio.is_some() {
+            self.stop_audio();
             println!("Stop recording");
-            //stream drop
         }
-    }
-    pub fn render(&mut self) {
-
-        let aspect_ratio = self.hardware.size.width as f32 / self.hardware.size.height as f32;
 
-        if !self.is_record {
-            self.counter += self.direction;
-            self.scale += self.direction as f32 / 1000.0;
+        println!("State: {previous:?} -> {next:?}");
+        true
+    }
+    pub fn toggle_record(&mut self) {
+        match self.assistant_state() {
+            AssistantState::Listening => self.set_assistant_state(AssistantState::Idle),
+            _ => self.set_assistant_state(AssistantState::Listening),
+        };
+    }
+    pub fn toggle_mute(&mut self) {
+        match self.assistant_state() {
+            AssistantState::Muted => self.set_assistant_state(AssistantState::Idle),
+            _ => self.set_assistant_state(AssistantState::Muted),
+        };
+    }
+    //Режим рации: пока клавиша зажата - слушаем. true, если состояние сменилось
+    pub fn push_to_talk(&mut self, held: bool) -> bool {
+        match (held, self.assistant_state() == AssistantState::Listening) {
+            (true, false) => self.set_assistant_state(AssistantState::Listening),
+            (false, true) => self.set_assistant_state(AssistantState::Idle),
+            _ => false,
         }
-        else {
-            if self.rotation >= 40.0 * PI {
-                self.rotation = 0.0;
+    }
+    fn start_audio(&mut self) -> Result<(), crate::audio::input::AudioError> {
+        let audio = AudioInput::start(&self.source)?;
+        self.spectrum = Some(Spectrum::new(
+            WINDOW_SIZE,
+            self.spectrum_bins,
+            audio.sample_rate(),
+            SPECTRUM_SMOOTHING,
+            SPECTRUM_PEAK_DECAY,
+        ));
+        self.audio = Some(audio);
+        self.level.reset();
+        self.waveform.reset();
+        Ok(())
+    }
+    fn stop_audio(&mut self) {
+        //Drop останавливает поток
+        self.audio = None;
+        self.spectrum = None;
+    }
+    //Уровень звука от бэкенда (например, озвучки ответа), используется без записи
+    pub fn set_level(&mut self, level: f32) {
+        self.external_level = level;
+    }
+    pub fn set_theme(&mut self, name: &str) -> bool {
+        match self.config.theme(name) {
+            Some(theme) => {
+                self.theme = theme;
+                self.theme_name = name.to_string();
+                true
             }
-            self.rotation += 0.03;
+            None => false,
         }
-
-        if self.counter == 0 || (self.counter == 300 && !self.is_record) {
-            self.direction = -self.direction;
+    }
+    //Применяет перечитанные настройки к работающему окну: геометрию, тему и анимации.
+    //Выбранная во время работы тема сохраняется, если в файле не сменили начальную
+    pub fn apply_config(&mut self, config: Config) {
+        if config.window.present_mode != self.config.window.present_mode {
+            let hardware = &mut self.hardware;
+            hardware.config.present_mode = setup::present_mode(&hardware.present_modes, config.window.present_mode);
+            //В config всегда последний ненулевой размер, так что настраивать можно и у свернутого окна
+            hardware.surface.configure(&hardware.device, &hardware.config);
+        }
+        if config.render != self.config.render {
+            //Число сэмплов и способ отрисовки колец зашиты в конвейеры, проще собрать рендерер заново
+            self.renderer = create_renderer(&self.hardware, &config, self.scale_factor);
         }
+        self.renderer.set_shape(&self.hardware.device, config.ring.shape());
+        self.animator.set_scale(config.ring.scale);
+        self.animator.set_speed(config.animation.speed);
+        self.animator.set_crossfade(config.animation.crossfade);
 
-        let vertex_buffers = buffers::create_vertex(&self.hardware.device);
-        let uniform_buffers = buffers::create_uniform(aspect_ratio, self.scale, self.rotation, &self.hardware.device);
+        if config.theme.name != self.config.theme.name {
+            self.theme_name = config.theme.name.clone();
+        }
+        self.theme = match config.theme(&self.theme_name) {
+            Some(theme) => theme,
+            None => {
+                self.theme_name = config.theme.name.clone();
+                config.initial_theme()
+            }
+        };
+        self.config = config;
+    }
+    //Следующая тема по кругу, встроенные и из файла настроек
+    pub fn cycle_theme(&mut self) {
+        let names = self.config.theme_names();
+        let next = names
+            .iter()
+            .position(|name| *name == self.theme_name)
+            .map_or(0, |index| (index + 1) % names.len());
+        let name = names[next].to_string();
+        self.set_theme(&name);
+        println!("Theme: {name}");
+    }
+    pub fn toggle_debug_overlay(&mut self) {
+        self.debug_overlay = !self.debug_overlay;
+    }
+    //Последний показанный кадр без меню и отладочного графика
+    pub fn screenshot(&self, path: &Path) -> Result<(), HeadlessError> {
+        let Some(scene) = &self.scene else {
+            return Err(HeadlessError::Io(path.to_path_buf(), std::io::Error::other("nothing has been drawn yet")));
+        };
+        let mut scene = scene.clone();
+        scene.overlay.clear();
+        let hardware = &self.hardware;
+        let antialiasing = setup::antialiasing(
+            &hardware.adapter,
+            &hardware.device,
+            renderer::scene_format(headless::FORMAT, &self.config.render),
+            self.config.render.antialiasing,
+        );
+        let render = RenderConfig { antialiasing, ..self.config.render };
+        headless::capture(&hardware.device, &hardware.queue, &scene, self.config.ring.shape(), render, self.size()).save_png(path)
+    }
+    //None - курсор ушел из окна
+    pub fn cursor_moved(&mut self, position: Option<(f64, f64)>) {
+        self.cursor = position;
+        let position = position.map(|position| self.logical(position));
+        if let Some(menu) = &mut self.menu {
+            menu.hover(position);
+        }
+    }
+    //Открытое меню забирает клики себе, щелчок мимо него просто закрывает меню
+    pub fn mouse_pressed(&mut self, button: MouseButton) -> Option<Action> {
+        let position = self.cursor?;
+        if let Some(menu) = self.menu.take() {
+            if button != MouseButton::Left {
+                return None;
+            }
+            return menu.item_at(self.logical(position)).map(|item| match item {
+                MenuItem::Mute => Action::Mute,
+                MenuItem::Theme => Action::CycleTheme,
+                MenuItem::Quit => Action::Quit,
+            });
+        }
+        match button {
+            MouseButton::Right => {
+                let muted = self.assistant_state() == AssistantState::Muted;
+                let position = self.logical(position);
+                let mut menu = Menu::open(position, self.logical_size(), muted);
+                menu.hover(Some(position));
+                self.menu = Some(menu);
+                None
+            }
+            MouseButton::Left if self.over_ring(position) => Some(Action::ToggleRecord),
+            MouseButton::Left => Some(Action::MoveWindow),
+            _ => None,
+        }
+    }
+    pub fn cycle_visual_mode(&mut self) {
+        self.mode = self.mode.next();
+        println!("Visual mode: {:?}", self.mode);
+    }
+    //Ошибка - только та, после которой рисовать дальше нельзя
+    pub fn render(&mut self) -> Result<(), SurfaceError> {
+        if self.visibility.is_paused() {
+            return Ok(());
+        }
 
-        let uniform_bind_group = &self.hardware.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &self.uniform_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: uniform_buffers[0].as_entire_binding(),
-                }, 
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: uniform_buffers[1].as_entire_binding(),
+        let dt = self.animator.tick();
+        let level = match &mut self.audio {
+            Some(audio) => {
+                audio.poll();
+                if let Some(spectrum) = &mut self.spectrum {
+                    spectrum.update(audio.samples(), dt);
                 }
-            ],
-            label: None,
-        }); 
-
-        let frame = self.hardware.surface.get_current_texture().unwrap();
-        let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let smaa_frame = self.smaa_target.start_frame(&self.hardware.device, &self.hardware.queue, &view);
-
-        let mut encoder = self.hardware.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {label: None});
-        {
-            let mut rpass =
-                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: None,
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &smaa_frame, 
-                        resolve_target: None, 
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                            store: wgpu::StoreOp::Store, 
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
-                });
-
-            rpass.set_pipeline(&self.render_pipeline);
-            rpass.set_bind_group(0, uniform_bind_group, &[]);
-
-            if !self.is_record {
-                rpass.set_vertex_buffer(0, vertex_buffers[0].slice(..));
-                rpass.set_index_buffer(self.index_buffers[0].0.slice(..), wgpu::IndexFormat::Uint16);
-                rpass.draw_indexed(0..self.index_buffers[0].1,0, 0..1);
-
-                rpass.set_vertex_buffer(0, vertex_buffers[1].slice(..));
-                rpass.set_index_buffer(self.index_buffers[0].0.slice(..), wgpu::IndexFormat::Uint16);
-                rpass.draw_indexed(0..self.index_buffers[0].1,0, 0..1);   
+                self.waveform.update(audio.samples());
+                self.level.update(audio.samples(), dt)
             }
-            else {
-                rpass.set_vertex_buffer(0, vertex_buffers[0].slice(..));
-                rpass.set_index_buffer(self.index_buffers[1].0.slice(..), wgpu::IndexFormat::Uint16);
-                rpass.draw_indexed(0..self.index_buffers[1].1,0, 0..1);
-
-                rpass.set_vertex_buffer(0, vertex_buffers[1].slice(..));
-                rpass.set_index_buffer(self.index_buffers[1].0.slice(..), wgpu::IndexFormat::Uint16);
-                rpass.draw_indexed(0..self.index_buffers[1].1,0, 0..1);   
+            None => self.level.follow(self.external_level, dt),
+        };
+        let frame = self.animator.frame(level, self.mode);
+        let (outer, inner) = self.audio_offsets();
+        //Прозрачность поверхности выбирается при запуске, смена overlay в настройках ждет перезапуска
+        let theme = if self.transparent { self.theme.transparent() } else { self.theme };
+        let mut scene = Scene::from_frame(theme, &frame, (&outer, &inner));
+
+        //Подсветка под курсором плавно нарастает и гаснет; при открытом меню кольцо не подсвечиваем
+        let hovered = self.menu.is_none() && self.cursor.is_some_and(|position| self.over_ring(position));
+        let target = if hovered { 1.0 } else { 0.0 };
+        self.hover += (target - self.hover) * smoothing_step(dt, HOVER_SMOOTHING);
+        let brightness = 1.0 + HOVER_BRIGHTNESS * self.hover;
+        for layer in &mut scene.layers {
+            for channel in &mut layer.tint[..3] {
+                *channel *= brightness;
             }
         }
-        self.hardware.queue.submit(Some(encoder.finish()));
-        smaa_frame.resolve();
+        //Переход занимает примерно длительность crossfade
+        let bloom = self.config.bloom.state(self.animator.state());
+        self.bloom = self.bloom.lerp(bloom, smoothing_step(dt, self.config.animation.crossfade / 3.0));
+        scene.bloom = self.bloom;
+
+        self.frames.push(dt);
+        if self.debug_overlay {
+            scene.overlay = self.frames.vertices(self.scale_factor);
+        }
+        if let Some(menu) = &self.menu {
+            scene.overlay.extend(menu.vertices(self.theme.accent, self.scale_factor));
+        }
+
+        let frame = match self.hardware.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(err) => {
+                return match surface::recovery(&err) {
+                    Recovery::Reconfigure => {
+                        self.hardware.surface.configure(&self.hardware.device, &self.hardware.config);
+                        Ok(())
+                    }
+                    Recovery::SkipFrame => Ok(()),
+                    Recovery::Exit => Err(err),
+                };
+            }
+        };
+        let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.renderer.render(&self.hardware.device, &self.hardware.queue, &view, &scene);
         frame.present();
+        self.scene = Some(scene);
+        Ok(())
+    }
+}
+
+impl State<'_> {
+    fn size(&self) -> (u32, u32) {
+        (self.hardware.config.width, self.hardware.config.height)
+    }
+    fn logical_size(&self) -> (u32, u32) {
+        let (width, height) = self.size();
+        PhysicalSize::new(width, height).to_logical::<u32>(self.scale_factor).into()
+    }
+    //Из физических пикселей окна в логические, в них живут меню и графики
+    fn logical(&self, position: (f64, f64)) -> (f64, f64) {
+        PhysicalPosition::new(position.0, position.1).to_logical::<f64>(self.scale_factor).into()
     }
+    fn over_ring(&self, position: (f64, f64)) -> bool {
+        let Some(scene) = &self.scene else { return false };
+        let shape = self.config.ring.shape();
+        scene.layers.iter().any(|layer| hit_test::hit_ring(layer, &shape, position, self.size()))
+    }
+    //Смещения сегментов внешнего и внутреннего колец для кольца, которое слушает звук
+    fn audio_offsets(&self) -> (Vec<f32>, Vec<f32>) {
+        match (&self.spectrum, self.mode) {
+            //Внешнее кольцо показывает удержанные пики, внутреннее - текущий спектр
+            (Some(spectrum), VisualMode::Spectrum) => (
+                spread(spectrum.peaks(), SPECTRUM_DEPTH),
+                spread(spectrum.bands(), SPECTRUM_DEPTH),
+            ),
+            (_, VisualMode::Waveform) => {
+                let offsets = spread(self.waveform.points(), WAVEFORM_DEPTH);
+                (offsets.clone(), offsets)
+            }
+            _ => (Vec::new(), Vec::new()),
+        }
+    }
+}
+
+//Растягиваем значения полос на все сегменты кольца
+fn spread(values: &[f32], depth: f32) -> Vec<f32> {
+    (0..SEGMENTS)
+        .map(|segment| values[segment * values.len() / SEGMENTS] * depth)
+        .collect()
+}
+
+fn create_renderer(hardware: &Preload, config: &Config, scale_factor: f64) -> Renderer {
+    let format = hardware.config.format;
+    let scene_format = renderer::scene_format(format, &config.render);
+    let antialiasing = setup::antialiasing(&hardware.adapter, &hardware.device, scene_format, config.render.antialiasing);
+    let mut renderer = Renderer::new(
+        &hardware.device,
+        &hardware.queue,
+        format,
+        hardware.config.width,
+        hardware.config.height,
+        config.ring.shape(),
+        RenderConfig { antialiasing, ..config.render },
+    );
+    renderer.set_scale_factor(scale_factor);
+    renderer
 }
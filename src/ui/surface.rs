@@ -0,0 +1,48 @@
+// Что делать, когда поверхность окна не отдает кадр, и когда рисовать вообще не нужно
+
+use wgpu::SurfaceError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    // Поверхность устарела (сменился размер, монитор, драйвер) - настраиваем заново, кадр пропускаем
+    Reconfigure,
+    // Кадр не успел освободиться - пропускаем, в следующем повезет
+    SkipFrame,
+    // Видеопамяти не хватает даже на кадр, продолжать бессмысленно
+    Exit,
+}
+
+pub fn recovery(err: &SurfaceError) -> Recovery {
+    match err {
+        SurfaceError::Lost | SurfaceError::Outdated => Recovery::Reconfigure,
+        SurfaceError::Timeout => Recovery::SkipFrame,
+        SurfaceError::OutOfMemory => Recovery::Exit,
+    }
+}
+
+// Свернутое (0x0) или полностью закрытое другими окно не рисуем
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visibility {
+    size: (u32, u32),
+    occluded: bool,
+}
+
+impl Visibility {
+    pub fn new(width: u32, height: u32) -> Visibility {
+        Visibility { size: (width, height), occluded: false }
+    }
+
+    //true - поверхность нужно настроить под новый размер; нулевой размер wgpu не принимает
+    pub fn resize(&mut self, width: u32, height: u32) -> bool {
+        self.size = (width, height);
+        width > 0 && height > 0
+    }
+
+    pub fn set_occluded(&mut self, occluded: bool) {
+        self.occluded = occluded;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.size.0 == 0 || self.size.1 == 0 || self.occluded
+    }
+}
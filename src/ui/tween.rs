@@ -0,0 +1,239 @@
+use std::f32::consts::{PI, TAU};
+
+// Кривые сглаживания, t и результат в диапазоне 0..1 (elastic и spring могут выходить за 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    Elastic,
+    // Затухающие колебания вокруг цели
+    Spring { damping: f32, frequency: f32 },
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseInOut => (1.0 - (PI * t).cos()) / 2.0,
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::Elastic => {
+                if t == 0.0 || t == 1.0 {
+                    return t;
+                }
+                2f32.powf(-10.0 * t) * ((10.0 * t - 0.75) * TAU / 3.0).sin() + 1.0
+            }
+            Easing::Spring { damping, frequency } => {
+                if t == 1.0 {
+                    return t;
+                }
+                1.0 - (-damping * t).exp() * (TAU * frequency * t).cos()
+            }
+        }
+    }
+}
+
+// Что можно анимировать
+pub trait Lerp: Copy {
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, other: f32, t: f32) -> f32 {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for [f32; 4] {
+    fn lerp(self, other: [f32; 4], t: f32) -> [f32; 4] {
+        std::array::from_fn(|i| self[i].lerp(other[i], t))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    // Остаемся на последнем ключе
+    Once,
+    Loop,
+    // Туда и обратно
+    PingPong,
+}
+
+// easing - кривая, по которой приходим к этому ключу от предыдущего
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+    pub easing: Easing,
+}
+
+// Дорожка ключевых кадров одного свойства
+#[derive(Debug, Clone)]
+pub struct Track<T> {
+    keys: Vec<Keyframe<T>>,
+    repeat: Repeat,
+}
+
+impl<T: Lerp> Track<T> {
+    //Дорожка начинается со значения start в момент 0
+    pub fn from(start: T) -> Track<T> {
+        Track {
+            keys: vec![Keyframe { time: 0.0, value: start, easing: Easing::Linear }],
+            repeat: Repeat::Once,
+        }
+    }
+
+    pub fn constant(value: T) -> Track<T> {
+        Track::from(value)
+    }
+
+    //Ключи добавляются по возрастанию времени
+    pub fn key(mut self, time: f32, value: T, easing: Easing) -> Track<T> {
+        debug_assert!(time >= self.duration(), "keyframes must be added in order");
+        self.keys.push(Keyframe { time, value, easing });
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Track<T> {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map_or(0.0, |key| key.time)
+    }
+
+    pub fn sample(&self, time: f32) -> T {
+        let duration = self.duration();
+        let time = if duration <= 0.0 {
+            0.0
+        } else {
+            match self.repeat {
+                Repeat::Once => time.clamp(0.0, duration),
+                Repeat::Loop => time.rem_euclid(duration),
+                Repeat::PingPong => {
+                    let time = time.rem_euclid(2.0 * duration);
+                    if time > duration { 2.0 * duration - time } else { time }
+                }
+            }
+        };
+
+        let next = self.keys.iter().position(|key| key.time > time);
+        match next {
+            Some(0) => self.keys[0].value,
+            Some(index) => {
+                let from = &self.keys[index - 1];
+                let to = &self.keys[index];
+                let t = (time - from.time) / (to.time - from.time);
+                from.value.lerp(to.value, to.easing.apply(t))
+            }
+            None => self.keys[self.keys.len() - 1].value,
+        }
+    }
+}
+
+// Значения свойств кольца в момент времени. scale - множитель к базовому масштабу,
+// radius - прибавка к радиусу без изменения толщины
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pose {
+    pub scale: f32,
+    pub rotation: f32,
+    pub radius: f32,
+    pub color: [f32; 4],
+    pub opacity: f32,
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Pose {
+            scale: 1.0,
+            rotation: 0.0,
+            radius: 0.0,
+            color: [1.0; 4],
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Pose {
+    //Наложение: множители перемножаются, смещения складываются
+    pub fn compose(&self, other: &Pose) -> Pose {
+        Pose {
+            scale: self.scale * other.scale,
+            rotation: self.rotation + other.rotation,
+            radius: self.radius + other.radius,
+            color: std::array::from_fn(|i| self.color[i] * other.color[i]),
+            opacity: self.opacity * other.opacity,
+        }
+    }
+}
+
+// Набор дорожек по свойствам; вложенные анимации накладываются через Pose::compose
+#[derive(Debug, Clone, Default)]
+pub struct Animation {
+    scale: Option<Track<f32>>,
+    rotation: Option<Track<f32>>,
+    radius: Option<Track<f32>>,
+    color: Option<Track<[f32; 4]>>,
+    opacity: Option<Track<f32>>,
+    layers: Vec<Animation>,
+}
+
+impl Animation {
+    pub fn new() -> Animation {
+        Animation::default()
+    }
+
+    pub fn scale(mut self, track: Track<f32>) -> Animation {
+        self.scale = Some(track);
+        self
+    }
+
+    pub fn rotation(mut self, track: Track<f32>) -> Animation {
+        self.rotation = Some(track);
+        self
+    }
+
+    pub fn radius(mut self, track: Track<f32>) -> Animation {
+        self.radius = Some(track);
+        self
+    }
+
+    pub fn color(mut self, track: Track<[f32; 4]>) -> Animation {
+        self.color = Some(track);
+        self
+    }
+
+    pub fn opacity(mut self, track: Track<f32>) -> Animation {
+        self.opacity = Some(track);
+        self
+    }
+
+    pub fn compose(mut self, other: Animation) -> Animation {
+        self.layers.push(other);
+        self
+    }
+
+    pub fn sample(&self, time: f32) -> Pose {
+        let identity = Pose::default();
+        let own = Pose {
+            scale: self.scale.as_ref().map_or(identity.scale, |track| track.sample(time)),
+            rotation: self.rotation.as_ref().map_or(identity.rotation, |track| track.sample(time)),
+            radius: self.radius.as_ref().map_or(identity.radius, |track| track.sample(time)),
+            color: self.color.as_ref().map_or(identity.color, |track| track.sample(time)),
+            opacity: self.opacity.as_ref().map_or(identity.opacity, |track| track.sample(time)),
+        };
+        self.layers.iter().fold(own, |pose, layer| pose.compose(&layer.sample(time)))
+    }
+}
@@ -1,11 +1,19 @@
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Vertex {
     position: [f32; 3],
     color: [f32; 4]
 }
 impl Vertex {
+    pub fn new(position: [f32; 3], color: [f32; 4]) -> Vertex {
+        Vertex { position, color }
+    }
+
+    pub fn map_color(self, map: impl Fn([f32; 4]) -> [f32; 4]) -> Vertex {
+        Vertex { position: self.position, color: map(self.color) }
+    }
+
     const ATTRIBS: [wgpu::VertexAttribute; 2] =
         wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
 
@@ -20,12 +28,33 @@ impl Vertex {
     }
 }
 
-pub fn generate_glow_ring(outer_radius: f32, inner_radius:f32, color_first: [f32; 4], color_second: [f32; 4]) -> Vec<Vertex> {
+// Кольцо состоит из 180 сегментов по 2 градуса
+pub const SEGMENTS: usize = 180;
+
+// Какой край полосы - контур кольца. Там шейдер сглаживает край, а на стыке двух полос - нет
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outline {
+    Outer,
+    Inner,
+}
+
+//offsets - радиальное смещение каждого сегмента, пустой срез дает ровное кольцо.
+//В z вершины - 0 на контуре и 1 на другом краю полосы, по нему шейдер находит расстояние до контура
+pub fn generate_glow_ring(outer_radius: f32, inner_radius:f32, color_first: [f32; 4], color_second: [f32; 4], offsets: &[f32], outline: Outline) -> Vec<Vertex> {
     
+    let offset = |segment: usize| offsets.get(segment).copied().unwrap_or(0.0);
+    //Шов на 0/360 градусах общий для первого и последнего сегмента
+    let seam = (offset(0) + offset(SEGMENTS - 1)) / 2.0;
+    let (outer_z, inner_z) = match outline {
+        Outline::Outer => (0.0, 1.0),
+        Outline::Inner => (1.0, 0.0),
+    };
+
     let mut positions = Vec::new();
 
     for i in (0..360).step_by(2) {
         let radians = (i as f32 + 1.0).to_radians();
+        let shift = offset(i / 2);
 
         let x = radians.cos();
         let y = radians.sin();
@@ -34,17 +63,16 @@ pub fn generate_glow_ring(outer_radius: f32, inner_radius:f32, color_first: [f32
             let radians = (i as f32).to_radians();
             let x = radians.cos();
             let y = radians.sin();
-            positions.push(Vertex {position: [x * (inner_radius), y * (inner_radius), 1.0], color: color_first}); 
-            positions.push(Vertex {position: [x * (outer_radius), y * (outer_radius), 1.0], color: color_second}); 
+            positions.push(Vertex {position: [x * (inner_radius + seam), y * (inner_radius + seam), inner_z], color: color_first}); 
+            positions.push(Vertex {position: [x * (outer_radius + seam), y * (outer_radius + seam), outer_z], color: color_second}); 
         }
-        positions.push(Vertex {position: [x * (outer_radius), y * (outer_radius), 1.0], color: color_second}); 
-        positions.push(Vertex {position: [x * (inner_radius), y * (inner_radius), 1.0], color: color_first}); 
+        positions.push(Vertex {position: [x * (outer_radius + shift), y * (outer_radius + shift), outer_z], color: color_second}); 
+        positions.push(Vertex {position: [x * (inner_radius + shift), y * (inner_radius + shift), inner_z], color: color_first}); 
     };
-    let x = (360 as f32).to_radians().cos();
-    let y = (360 as f32).to_radians().sin();
-    positions.push(Vertex {position: [x * (outer_radius), y *  (outer_radius), 1.0], color: color_second});
-    positions.push(Vertex {position: [x * (inner_radius), y * (inner_radius), 1.0], color: color_first});
+    let x = 360_f32.to_radians().cos();
+    let y = 360_f32.to_radians().sin();
+    positions.push(Vertex {position: [x * (outer_radius + seam), y *  (outer_radius + seam), outer_z], color: color_second});
+    positions.push(Vertex {position: [x * (inner_radius + seam), y * (inner_radius + seam), inner_z], color: color_first});
 
     positions
 }
-
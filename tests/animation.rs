@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use render::ui::animator::{Animator, Frame, BASE_SCALE, CROSSFADE_SECONDS};
+use render::ui::clock::ManualClock;
+use render::ui::lifecycle::{AssistantState, VisualMode};
+
+//Крутим аниматор с заданной частотой кадров, переход в next - в момент switch_at
+fn run(rate: u32, seconds: f32, next: Option<(AssistantState, f32)>) -> Frame {
+    let clock = ManualClock::new();
+    let mut animator = Animator::new(Box::new(clock.clone()), AssistantState::Idle);
+    let step = Duration::from_secs_f64(1.0 / rate as f64);
+    let frames = (seconds * rate as f32).round() as u32;
+
+    let mut pending = next;
+    for frame in 0..frames {
+        if let Some((state, at)) = pending {
+            if frame as f32 / rate as f32 >= at {
+                animator.transition(state);
+                pending = None;
+            }
+        }
+        clock.advance(step);
+        animator.tick();
+    }
+    animator.frame(0.0, VisualMode::Dashed)
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-3, "{a} != {b}");
+}
+
+#[test]
+fn breathing_does_not_depend_on_frame_rate() {
+    let reference = run(60, 1.5, None);
+    for rate in [30, 144] {
+        assert_close(run(rate, 1.5, None).layers[0].pose.scale, reference.layers[0].pose.scale);
+    }
+}
+
+#[test]
+fn spinning_does_not_depend_on_frame_rate() {
+    let thinking = Some((AssistantState::Thinking, 0.0));
+    let reference = run(60, 2.0, thinking);
+    assert!(reference.layers[0].pose.rotation > 0.0);
+    for rate in [30, 144] {
+        assert_close(run(rate, 2.0, thinking).layers[0].pose.rotation, reference.layers[0].pose.rotation);
+    }
+}
+
+#[test]
+fn idle_breathing_peaks_after_five_seconds() {
+    assert_close(run(60, 0.0, None).layers[0].pose.scale, BASE_SCALE);
+    assert_close(run(60, 5.0, None).layers[0].pose.scale, BASE_SCALE + 0.17);
+    assert_close(run(60, 10.0, None).layers[0].pose.scale, BASE_SCALE);
+}
+
+#[test]
+fn crossfade_finishes_in_fixed_time() {
+    let halfway = run(144, CROSSFADE_SECONDS / 2.0, Some((AssistantState::Speaking, 0.0)));
+    assert_eq!(halfway.layers.len(), 2);
+    assert_close(halfway.layers[0].pose.opacity, 0.5);
+    assert_close(halfway.layers[1].pose.opacity, 0.5);
+
+    for rate in [30, 60, 144] {
+        let done = run(rate, CROSSFADE_SECONDS + 0.05, Some((AssistantState::Speaking, 0.0)));
+        assert_eq!(done.layers.len(), 1);
+        assert_eq!(done.layers[0].pose.opacity, 1.0);
+    }
+}
+
+#[test]
+fn paused_clock_freezes_motion() {
+    let clock = ManualClock::new();
+    let mut animator = Animator::new(Box::new(clock.clone()), AssistantState::Thinking);
+    clock.advance(Duration::from_millis(250));
+    animator.tick();
+    let before = animator.frame(0.0, VisualMode::Dashed);
+
+    animator.tick();
+    let after = animator.frame(0.0, VisualMode::Dashed);
+    assert_eq!(before.layers[0].pose, after.layers[0].pose);
+}
+
+#[test]
+fn level_grows_reactive_states_only() {
+    let clock = ManualClock::new();
+    let listening = Animator::new(Box::new(clock.clone()), AssistantState::Listening);
+    let idle = Animator::new(Box::new(clock), AssistantState::Idle);
+
+    assert!(listening.frame(1.0, VisualMode::Spectrum).layers[0].pose.scale > BASE_SCALE + 0.23);
+    assert_close(idle.frame(1.0, VisualMode::Spectrum).layers[0].pose.scale, BASE_SCALE);
+}
+
+#[test]
+fn only_defined_transitions_are_allowed() {
+    use AssistantState::*;
+
+    let allowed = [
+        (Idle, Listening), (Idle, Thinking), (Idle, Speaking), (Idle, Error), (Idle, Muted),
+        (Listening, Idle), (Listening, Thinking), (Listening, Error), (Listening, Muted),
+        (Thinking, Idle), (Thinking, Listening), (Thinking, Speaking), (Thinking, Error), (Thinking, Muted),
+        (Speaking, Idle), (Speaking, Listening), (Speaking, Error), (Speaking, Muted),
+        (Error, Idle), (Muted, Idle), (Muted, Error),
+    ];
+    let states = [Idle, Listening, Thinking, Speaking, Error, Muted];
+    for from in states {
+        for to in states {
+            assert_eq!(from.can_transition_to(to), allowed.contains(&(from, to)), "{from:?} -> {to:?}");
+        }
+    }
+}
+
+#[test]
+fn illegal_transition_keeps_the_current_look() {
+    let clock = ManualClock::new();
+    let mut animator = Animator::new(Box::new(clock.clone()), AssistantState::Idle);
+    assert!(animator.transition(AssistantState::Muted));
+    clock.advance(Duration::from_secs(1));
+    animator.tick();
+
+    assert!(!animator.transition(AssistantState::Listening));
+    assert!(!animator.transition(AssistantState::Muted));
+    assert_eq!(animator.state(), AssistantState::Muted);
+    let frame = animator.frame(0.0, VisualMode::Dashed);
+    assert_eq!(frame.layers.len(), 1);
+
+    assert!(animator.transition(AssistantState::Idle));
+    assert_eq!(animator.state(), AssistantState::Idle);
+}
@@ -0,0 +1,116 @@
+use std::f32::consts::TAU;
+
+use render::audio::input::InputSource;
+use render::audio::level::LevelMeter;
+use render::audio::spectrum::Spectrum;
+use render::audio::waveform::Waveform;
+
+const RATE: f32 = 48_000.0;
+
+//Синус частоты frequency, phase - сдвиг в сэмплах
+fn sine(frequency: f32, amplitude: f32, len: usize, phase: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| amplitude * (TAU * frequency * (i + phase) as f32 / RATE).sin())
+        .collect()
+}
+
+#[test]
+fn level_is_zero_for_silence_and_full_for_a_full_scale_sine() {
+    let mut meter = LevelMeter::new(0.025, 0.2);
+    assert_eq!(meter.update(&[0.0; 1024], 1.0), 0.0);
+    let level = meter.update(&sine(1000.0, 1.0, 4800, 0), 1.0);
+    assert!((level - 1.0).abs() < 0.01, "{level}");
+    assert!(meter.update(&[], 1.0) < 0.01);
+}
+
+#[test]
+fn level_attacks_faster_than_it_releases() {
+    let loud = sine(1000.0, 1.0, 4800, 0);
+    let mut meter = LevelMeter::new(0.025, 0.2);
+    let rise = meter.update(&loud, 0.025);
+
+    meter.update(&loud, 1.0);
+    let fall = 1.0 - meter.update(&[0.0; 1024], 0.025);
+    assert!(rise > 0.5, "{rise}");
+    assert!(rise > 3.0 * fall, "rise {rise}, fall {fall}");
+}
+
+fn loudest(values: &[f32]) -> usize {
+    (0..values.len()).max_by(|&a, &b| values[a].total_cmp(&values[b])).unwrap()
+}
+
+#[test]
+fn sine_lands_in_its_log_spaced_band() {
+    //64 полосы от 40 Гц до 16 кГц: 1 кГц попадает в полосу 64 * ln(25) / ln(400) = 34
+    let mut spectrum = Spectrum::new(2048, 64, RATE as u32, 0.033, 0.6);
+    spectrum.update(&sine(1000.0, 1.0, 2048, 0), 1.0);
+    assert_eq!(loudest(spectrum.bands()), 34);
+    assert!(spectrum.bands()[34] > 0.9);
+    assert!(spectrum.bands()[10] < 0.1);
+    assert!(spectrum.bands()[60] < 0.1);
+}
+
+#[test]
+fn peaks_hold_and_decay() {
+    let mut spectrum = Spectrum::new(2048, 64, RATE as u32, 0.033, 0.6);
+    spectrum.update(&sine(1000.0, 1.0, 2048, 0), 1.0);
+    let peak = spectrum.peaks()[34];
+
+    spectrum.update(&[0.0; 2048], 0.1);
+    let held = spectrum.peaks()[34];
+    assert!(held > spectrum.bands()[34]);
+    assert!((peak - held - 0.06).abs() < 1e-4, "{peak} -> {held}");
+
+    for _ in 0..20 {
+        spectrum.update(&[0.0; 2048], 0.1);
+    }
+    assert!(spectrum.peaks()[34] < 0.01);
+}
+
+#[test]
+fn band_count_is_kept_in_range() {
+    let mut empty = Spectrum::new(2048, 0, RATE as u32, 0.033, 0.6);
+    empty.update(&sine(1000.0, 1.0, 2048, 0), 1.0);
+    assert_eq!(empty.bands().len(), 1);
+    assert!(empty.bands()[0] > 0.9);
+
+    //Больше полос, чем сегментов кольца и бинов в нижних октавах: у каждой полосы хотя бы один бин
+    let mut dense = Spectrum::new(2048, 500, RATE as u32, 0.033, 0.6);
+    dense.update(&sine(1000.0, 1.0, 2048, 0), 1.0);
+    assert_eq!(dense.bands().len(), 500);
+    assert_eq!(dense.peaks().len(), 500);
+    assert!(dense.bands().iter().all(|band| band.is_finite()));
+    assert!(dense.bands().iter().any(|&band| band > 0.9));
+}
+
+#[test]
+fn trigger_aligns_phase_shifted_windows() {
+    //Полсэмпла сдвига, чтобы ни один сэмпл не лег ровно в ноль
+    let signal = |t: f32| (TAU * t / 48.0).sin() + 0.3 * (TAU * t / 16.0).sin();
+    let window = |shift: usize| -> Vec<f32> { (0..1024).map(|i| signal((i + shift) as f32 + 0.5)).collect() };
+    let mut first = Waveform::new(256, 32);
+    let mut second = Waveform::new(256, 32);
+    first.update(&window(0));
+    second.update(&window(17));
+    for (a, b) in first.points().iter().zip(second.points()) {
+        assert!((a - b).abs() < 1e-3, "{:?} != {:?}", first.points(), second.points());
+    }
+    assert!(first.points().iter().any(|point| point.abs() > 0.5));
+}
+
+#[test]
+fn window_without_zero_crossing_is_still_drawn() {
+    let mut waveform = Waveform::new(256, 32);
+    waveform.update(&[0.5; 1024]);
+    assert!(waveform.points().iter().all(|&point| point == 0.5));
+
+    //Сэмплов меньше окна - остается прошлая форма
+    waveform.update(&[-0.5; 100]);
+    assert!(waveform.points().iter().all(|&point| point == 0.5));
+}
+
+#[test]
+fn microphone_is_available_only_with_the_feature() {
+    assert_eq!(InputSource::Microphone.is_available(), cfg!(feature = "microphone"));
+    assert!(InputSource::Wav("voice.wav".into()).is_available());
+}
@@ -0,0 +1,14 @@
+//Общее для тестов отрисовки. Без программного адаптера (lavapipe/llvmpipe) они падают, иначе на CI
+//без него все прошло бы, не сравнив ни одного пикселя. Пропустить их можно только явно:
+//JARVIS_SKIP_GPU_TESTS=1 cargo test
+
+pub const SKIP_GPU_TESTS: &str = "JARVIS_SKIP_GPU_TESTS";
+
+//Вызывается, когда адаптера нет: паника, если пропуск не разрешен явно
+pub fn no_adapter(test: &str) {
+    if std::env::var_os(SKIP_GPU_TESTS).is_some_and(|value| value == "1") {
+        eprintln!("skipping {test}: no graphics adapter");
+        return;
+    }
+    panic!("{test}: no graphics adapter, not even a software one; install lavapipe/llvmpipe or set {SKIP_GPU_TESTS}=1 to skip");
+}
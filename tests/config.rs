@@ -0,0 +1,187 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use render::config::watcher::Watcher;
+use render::config::{Antialiasing, Config, ConfigError, PresentMode, RingGeometry};
+use render::ui::action::Action;
+use render::ui::colors;
+use render::ui::lifecycle::AssistantState;
+use winit::keyboard::{KeyCode, ModifiersState};
+
+fn invalid_key(text: &str) -> String {
+    match text.parse::<Config>() {
+        Err(ConfigError::Invalid { key, .. }) => key,
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+}
+
+fn parse_error(text: &str) -> String {
+    match text.parse::<Config>() {
+        Err(err @ ConfigError::Parse(_)) => err.to_string(),
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn empty_file_gives_defaults() {
+    assert_eq!("".parse::<Config>().unwrap(), Config::default());
+}
+
+#[test]
+fn example_file_documents_the_defaults() {
+    let text = include_str!("../config.example.toml");
+    assert_eq!(text.parse::<Config>().unwrap(), Config::default());
+}
+
+#[test]
+fn partial_sections_keep_other_defaults() {
+    let config: Config = "[ring]\nouter = 0.5\n[window]\ntitle = \"Friday\"".parse().unwrap();
+    assert_eq!(config.ring.outer, 0.5);
+    assert_eq!(config.ring.middle, 0.4);
+    assert_eq!(config.window.title, "Friday");
+    assert_eq!(config.window.width, 400);
+}
+
+#[test]
+fn themes_extend_and_override_builtins() {
+    let config: Config = "[theme]\nname = \"teal\"\nbackground = \"#102030\"\n[themes]\nteal = \"#00ff80\"\ncyan = \"#ff000080\""
+        .parse()
+        .unwrap();
+
+    let teal = config.initial_theme();
+    assert_eq!(teal.accent, [0.0, 1.0, 128.0 / 255.0, 1.0]);
+    assert_eq!(teal.background, [16.0 / 255.0, 32.0 / 255.0, 48.0 / 255.0, 1.0]);
+    assert_eq!(config.theme("cyan").unwrap().accent, [1.0, 0.0, 0.0, 128.0 / 255.0]);
+    assert_eq!(config.theme("amber").unwrap().accent, colors::AMBER);
+    assert!(config.theme("pink").is_none());
+}
+
+#[test]
+fn keys_accept_names_in_any_case() {
+    let config: Config = "[keys]\nrecord = \"space\"\nquit = \"f10\"".parse().unwrap();
+    assert_eq!(config.keys.record.to_string(), "Space");
+    assert_eq!(config.keys.quit.to_string(), "F10");
+}
+
+#[test]
+fn present_mode_is_read_by_name() {
+    let config: Config = "[window]\npresent_mode = \"mailbox\"".parse().unwrap();
+    assert_eq!(config.window.present_mode, PresentMode::Mailbox);
+
+    let unknown = parse_error("[window]\npresent_mode = \"adaptive\"");
+    assert!(unknown.contains("unknown variant `adaptive`"), "{unknown}");
+}
+
+#[test]
+fn render_settings_are_read_by_name() {
+    assert_eq!(Config::default().render.antialiasing, Antialiasing::Smaa);
+    let config: Config = "[render]\nantialiasing = \"msaa8\"".parse().unwrap();
+    assert_eq!(config.render.antialiasing, Antialiasing::Msaa8);
+    assert_eq!(config.render.antialiasing.sample_count(), 8);
+    assert_eq!(config.render.geometry, RingGeometry::Mesh);
+
+    let config: Config = "[render]\ngeometry = \"sdf\"".parse().unwrap();
+    assert_eq!(config.render.geometry, RingGeometry::Sdf);
+}
+
+#[test]
+fn bloom_is_set_per_state() {
+    let config: Config = "[render]\nbloom = true\n[bloom.error]\nintensity = 2.0".parse().unwrap();
+    assert!(config.render.bloom);
+    assert_eq!(config.bloom.state(AssistantState::Error).intensity, 2.0);
+    //Остальные ключи и состояния - по умолчанию
+    assert_eq!(config.bloom.state(AssistantState::Error).radius, 1.0);
+    assert_eq!(config.bloom.state(AssistantState::Idle), Config::default().bloom.idle);
+}
+
+#[test]
+fn chords_map_to_actions() {
+    let config: Config = "[keys]\nscreenshot = \"ctrl+shift+s\"\ntheme = \"Control+R\"".parse().unwrap();
+    assert_eq!(config.keys.screenshot.to_string(), "Ctrl+Shift+S");
+
+    let keys = config.keys;
+    let ctrl = ModifiersState::CONTROL;
+    assert_eq!(keys.action(KeyCode::KeyR, ModifiersState::empty()), Some(Action::ToggleRecord));
+    assert_eq!(keys.action(KeyCode::KeyR, ctrl), Some(Action::CycleTheme));
+    assert_eq!(keys.action(KeyCode::KeyS, ctrl | ModifiersState::SHIFT), Some(Action::Screenshot));
+    //Лишний модификатор - уже другое сочетание
+    assert_eq!(keys.action(KeyCode::KeyR, ctrl | ModifiersState::ALT), None);
+    assert_eq!(keys.action(KeyCode::F3, ModifiersState::empty()), Some(Action::ToggleDebugOverlay));
+}
+
+#[test]
+fn validation_points_to_the_bad_key() {
+    assert_eq!(invalid_key("[window]\nwidth = 0"), "window.width");
+    assert_eq!(invalid_key("[theme]\nname = \"pink\""), "theme.name");
+    assert_eq!(invalid_key("[ring]\nmiddle = 0.5"), "ring.outer");
+    assert_eq!(invalid_key("[ring]\ninner = 0.4"), "ring.middle");
+    assert_eq!(invalid_key("[ring]\ndash = [0, 4]"), "ring.dash");
+    assert_eq!(invalid_key("[ring]\nfalloff = 0.0"), "ring.falloff");
+    assert_eq!(invalid_key("[ring]\ngradient = 1.5"), "ring.gradient");
+    assert_eq!(invalid_key("[animation]\nspeed = 0.0"), "animation.speed");
+    assert_eq!(invalid_key("[bloom.thinking]\nradius = 0.0"), "bloom.thinking.radius");
+    assert_eq!(invalid_key("[keys]\nmute = \"R\""), "keys.mute");
+    assert_eq!(invalid_key("[keys]\nmute = \"Ctrl+V\"\nquit = \"control+v\""), "keys.quit");
+}
+
+#[test]
+fn nan_and_infinity_are_rejected() {
+    for key in ["inner", "middle", "outer", "scale", "falloff", "gradient"] {
+        assert_eq!(invalid_key(&format!("[ring]\n{key} = nan")), format!("ring.{key}"));
+    }
+    assert_eq!(invalid_key("[ring]\nouter = inf"), "ring.outer");
+    assert_eq!(invalid_key("[ring]\nscale = inf"), "ring.scale");
+    assert_eq!(invalid_key("[animation]\nspeed = nan"), "animation.speed");
+    assert_eq!(invalid_key("[animation]\ncrossfade = nan"), "animation.crossfade");
+    assert_eq!(invalid_key("[animation]\ncrossfade = inf"), "animation.crossfade");
+    for key in ["intensity", "threshold", "radius"] {
+        assert_eq!(invalid_key(&format!("[bloom.idle]\n{key} = nan")), format!("bloom.idle.{key}"));
+        assert_eq!(invalid_key(&format!("[bloom.muted]\n{key} = inf")), format!("bloom.muted.{key}"));
+    }
+}
+
+#[test]
+fn parse_errors_name_the_key_and_line() {
+    let unknown = parse_error("[ring]\nouter = 0.5\nthickness = 2");
+    assert!(unknown.contains("line 3"), "{unknown}");
+    assert!(unknown.contains("unknown field `thickness`"), "{unknown}");
+
+    let color = parse_error("[theme]\nbackground = \"black\"");
+    assert!(color.contains("line 2"), "{color}");
+    assert!(color.contains("invalid color `black`"), "{color}");
+
+    let key = parse_error("[keys]\n\nrecord = \"Hyper\"");
+    assert!(key.contains("line 3"), "{key}");
+    assert!(key.contains("unknown key `Hyper`"), "{key}");
+
+    let modifier = parse_error("[keys]\nquit = \"Hyper+Q\"");
+    assert!(modifier.contains("unknown modifier `Hyper` in `Hyper+Q`"), "{modifier}");
+
+    let kind = parse_error("[window]\nwidth = \"wide\"");
+    assert!(kind.contains("line 2"), "{kind}");
+}
+
+#[test]
+fn watcher_reloads_changes_and_reports_errors() {
+    let path = std::env::temp_dir().join(format!("jarvis-test-{}-config.toml", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let (sender, receiver) = mpsc::channel();
+    let _watcher = Watcher::start(&path, move |config| {
+        let _ = sender.send(config);
+    });
+    let next = || receiver.recv_timeout(Duration::from_secs(5)).expect("watcher did not notice the change");
+
+    //Файл появился после запуска
+    std::fs::write(&path, "[ring]\nscale = 2.0\n").unwrap();
+    assert_eq!(next().unwrap().ring.scale, 2.0);
+
+    std::fs::write(&path, "[ring]\nscale = -1.0\n").unwrap();
+    assert!(matches!(next(), Err(ConfigError::Invalid { key, .. }) if key == "ring.scale"));
+
+    std::fs::write(&path, "[window]\ntitle = \"Friday\"\n").unwrap();
+    assert_eq!(next().unwrap().window.title, "Friday");
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(receiver.recv_timeout(Duration::from_millis(600)).is_err());
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use render::config::Config;
+use render::ui::export::{self, Clip, Format};
+use render::ui::headless::{HeadlessError, Image};
+use render::ui::lifecycle::{AssistantState, VisualMode};
+
+fn output(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("jarvis-test-{}-{name}", std::process::id()))
+}
+
+fn clip(transparent: bool, loops: u32) -> Clip {
+    Clip {
+        state: AssistantState::Thinking,
+        mode: VisualMode::Dashed,
+        level: 0.0,
+        duration: 0.5,
+        fps: 8,
+        width: 64,
+        height: 48,
+        transparent,
+        loops,
+    }
+}
+
+//Без адаптера тест падает, если пропуск не разрешен явно, как и остальные тесты отрисовки
+fn export(path: &Path, format: Format, clip: &Clip) -> Option<u32> {
+    match pollster::block_on(export::export(path, format, Config::default(), clip)) {
+        Ok(frames) => Some(frames),
+        Err(HeadlessError::NoAdapter) => {
+            common::no_adapter("export");
+            None
+        }
+        Err(err) => panic!("{err}"),
+    }
+}
+
+#[test]
+fn format_follows_extension() {
+    assert_eq!(Format::from_path(Path::new("clip.gif")), Some(Format::Gif));
+    assert_eq!(Format::from_path(Path::new("clip.PNG")), Some(Format::Apng));
+    assert_eq!(Format::from_path(Path::new("clip.apng")), Some(Format::Apng));
+    assert_eq!(Format::from_path(Path::new("frames")), Some(Format::PngSequence));
+    assert_eq!(Format::from_path(Path::new("clip.mp4")), None);
+}
+
+#[test]
+fn apng_has_every_frame_and_loop_count() {
+    let path = output("clip.png");
+    let Some(frames) = export(&path, Format::Apng, &clip(false, 3)) else { return };
+    assert_eq!(frames, 4);
+
+    let reader = png::Decoder::new(File::open(&path).unwrap()).read_info().unwrap();
+    let animation = reader.info().animation_control.unwrap();
+    assert_eq!((animation.num_frames, animation.num_plays), (4, 3));
+    assert_eq!((reader.info().width, reader.info().height), (64, 48));
+    std::fs::remove_file(&path).unwrap();
+}
+
+//Задержки кадров GIF в сотых долях секунды и повтор
+fn gif_delays(name: &str, clip: &Clip) -> Option<(Vec<u16>, gif::Repeat)> {
+    let path = output(name);
+    let frames = export(&path, Format::Gif, clip)?;
+
+    let mut decoder = gif::DecodeOptions::new().read_info(File::open(&path).unwrap()).unwrap();
+    let mut delays = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        delays.push(frame.delay);
+    }
+    assert_eq!(delays.len(), frames as usize);
+    let repeat = decoder.repeat();
+    std::fs::remove_file(&path).unwrap();
+    Some((delays, repeat))
+}
+
+#[test]
+fn gif_frames_have_the_requested_delay() {
+    //8 fps - 12.5 сотых на кадр, поровну округлить нельзя
+    let Some((delays, repeat)) = gif_delays("clip.gif", &clip(false, 0)) else { return };
+    assert_eq!(delays, [13, 12, 13, 12]);
+    assert_eq!(repeat, gif::Repeat::Infinite);
+}
+
+#[test]
+fn gif_length_matches_duration_when_fps_does_not_divide_100() {
+    let clip = Clip { fps: 30, duration: 0.4, ..clip(false, 0) };
+    let Some((delays, _)) = gif_delays("thirty.gif", &clip) else { return };
+    assert_eq!(delays.len(), 12);
+    assert!(delays.iter().all(|&delay| delay == 3 || delay == 4), "{delays:?}");
+    assert_eq!(delays.iter().sum::<u16>(), 40);
+}
+
+#[test]
+fn transparent_sequence_keeps_background_clear() {
+    let dir = output("frames");
+    let Some(frames) = export(&dir, Format::PngSequence, &clip(true, 0)) else { return };
+
+    for index in 0..frames as usize {
+        let image = Image::load_png(&export::frame_path(&dir, index)).unwrap();
+        assert_eq!(&image.pixels[..4], &[0, 0, 0, 0]);
+        assert!(image.pixels.chunks(4).any(|p| p[3] > 128));
+    }
+    assert!(!export::frame_path(&dir, frames as usize).exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn oversized_clip_reports_the_device_limit() {
+    let path = output("huge.gif");
+    let huge = Clip { width: 40_000, height: 10, ..clip(false, 0) };
+    match pollster::block_on(export::export(&path, Format::Gif, Config::default(), &huge)) {
+        Err(HeadlessError::TooLarge { width, height, limit }) => {
+            assert_eq!((width, height), (40_000, 10));
+            assert!(limit < 40_000);
+            let message = HeadlessError::TooLarge { width, height, limit }.to_string();
+            assert!(message.contains("40000x10") && message.contains(&limit.to_string()), "{message}");
+        }
+        Err(HeadlessError::NoAdapter) => common::no_adapter("export"),
+        other => panic!("expected a size error, got {other:?}"),
+    }
+    assert!(!path.exists());
+}
@@ -0,0 +1,221 @@
+//Эталонные кадры каждого состояния на программном адаптере. Эталоны лежат в tests/golden,
+//при расхождении в target/golden пишутся фактический кадр и картинка различий.
+//Обновить эталоны после намеренного изменения картинки: UPDATE_GOLDEN=1 cargo test --test golden
+
+use std::path::{Path, PathBuf};
+
+mod common;
+
+use render::config::{Antialiasing, Config, RingGeometry};
+use render::ui::headless::{Headless, HeadlessError, Image, Shot};
+use render::ui::lifecycle::AssistantState;
+
+const WIDTH: u32 = 200;
+const HEIGHT: u32 = 540;
+// Порог различия цвета в пространстве YIQ (0..1), как в pixelmatch
+const COLOR_THRESHOLD: f32 = 0.1;
+// Какая доля пикселей может отличаться сверх порога: края сглаживания на разных драйверах
+const MAX_DIFFERENT: f32 = 0.002;
+
+fn references() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn failures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("target").join("golden")
+}
+
+fn check(name: &str, shot: Shot) {
+    check_with(name, shot, Config::default());
+}
+
+fn check_antialiasing(name: &str, shot: Shot, antialiasing: Antialiasing) {
+    let mut config = Config::default();
+    config.render.antialiasing = antialiasing;
+    check_with(name, shot, config);
+}
+
+fn check_sdf(name: &str, shot: Shot) {
+    let mut config = Config::default();
+    config.render.geometry = RingGeometry::Sdf;
+    check_with(name, shot, config);
+}
+
+fn check_bloom(name: &str, shot: Shot, config: Config) {
+    let mut config = config;
+    config.render.bloom = true;
+    check_with(name, shot, config);
+}
+
+fn check_with(name: &str, shot: Shot, config: Config) {
+    let mut headless = match pollster::block_on(Headless::new(config, WIDTH, HEIGHT, true)) {
+        Ok(headless) => headless,
+        Err(HeadlessError::NoAdapter) => return common::no_adapter(name),
+        Err(err) => panic!("{name}: {err}"),
+    };
+    let actual = headless.render(&shot);
+    let reference = references().join(format!("{name}.png"));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(references()).unwrap();
+        actual.save_png(&reference).unwrap();
+        return;
+    }
+
+    let expected = Image::load_png(&reference)
+        .unwrap_or_else(|err| panic!("no reference for {name} ({err}), run with UPDATE_GOLDEN=1 to create it"));
+    assert_eq!((actual.width, actual.height), (expected.width, expected.height), "{name}: size changed");
+
+    let (different, diff) = compare(&expected, &actual);
+    let allowed = (MAX_DIFFERENT * (WIDTH * HEIGHT) as f32) as usize;
+    if different > allowed {
+        std::fs::create_dir_all(failures()).unwrap();
+        let actual_path = failures().join(format!("{name}-actual.png"));
+        let diff_path = failures().join(format!("{name}-diff.png"));
+        actual.save_png(&actual_path).unwrap();
+        diff.save_png(&diff_path).unwrap();
+        panic!(
+            "{name}: {different} pixels differ from the reference (allowed {allowed}), see {} and {}",
+            actual_path.display(),
+            diff_path.display(),
+        );
+    }
+}
+
+//Число заметно отличающихся пикселей и картинка различий: эталон бледно, отличия красным
+fn compare(expected: &Image, actual: &Image) -> (usize, Image) {
+    let max_delta = 35215.0 * COLOR_THRESHOLD * COLOR_THRESHOLD;
+    let mut different = 0;
+    let mut pixels = Vec::with_capacity(expected.pixels.len());
+
+    for (e, a) in expected.pixels.chunks(4).zip(actual.pixels.chunks(4)) {
+        if color_delta(e, a) > max_delta {
+            different += 1;
+            pixels.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let gray = 255 - ((255 - luma(e) as u32) / 10) as u8;
+            pixels.extend_from_slice(&[gray, gray, gray, 255]);
+        }
+    }
+    (different, Image { width: expected.width, height: expected.height, pixels })
+}
+
+fn luma(p: &[u8]) -> u8 {
+    yiq(p).0 as u8
+}
+
+fn yiq(p: &[u8]) -> (f32, f32, f32) {
+    let (r, g, b) = (p[0] as f32, p[1] as f32, p[2] as f32);
+    (
+        r * 0.298_895_3 + g * 0.586_622_5 + b * 0.114_482_2,
+        r * 0.595_978 - g * 0.274_176_1 - b * 0.321_801_9,
+        r * 0.211_470_2 - g * 0.522_617_1 + b * 0.311_146_9,
+    )
+}
+
+//Квадрат разницы цветов с весами восприятия яркости и цветности
+fn color_delta(expected: &[u8], actual: &[u8]) -> f32 {
+    let (ey, ei, eq) = yiq(expected);
+    let (ay, ai, aq) = yiq(actual);
+    let (y, i, q) = (ey - ay, ei - ai, eq - aq);
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+#[test]
+fn idle() {
+    check("idle", Shot::new(AssistantState::Idle, 0.0));
+}
+
+#[test]
+fn idle_breathing_in() {
+    check("idle-breathing", Shot::new(AssistantState::Idle, 5.0));
+}
+
+#[test]
+fn listening() {
+    check("listening", Shot::new(AssistantState::Listening, 1.5));
+}
+
+#[test]
+fn listening_loud() {
+    check("listening-loud", Shot { level: 1.0, ..Shot::new(AssistantState::Listening, 1.5) });
+}
+
+#[test]
+fn thinking() {
+    check("thinking", Shot::new(AssistantState::Thinking, 0.7));
+}
+
+#[test]
+fn speaking() {
+    check("speaking", Shot::new(AssistantState::Speaking, 1.0));
+}
+
+#[test]
+fn error() {
+    check("error", Shot::new(AssistantState::Error, 0.25));
+}
+
+#[test]
+fn muted() {
+    check("muted", Shot::new(AssistantState::Muted, 1.0));
+}
+
+//Штрихи думающего кольца при каждом способе сглаживания, по умолчанию SMAA - в thinking
+#[test]
+fn thinking_without_antialiasing() {
+    check_antialiasing("thinking-none", Shot::new(AssistantState::Thinking, 0.7), Antialiasing::None);
+}
+
+#[test]
+fn thinking_analytic() {
+    check_antialiasing("thinking-analytic", Shot::new(AssistantState::Thinking, 0.7), Antialiasing::Analytic);
+}
+
+//Контур сплошного кольца шейдер тоже сглаживает
+#[test]
+fn idle_analytic() {
+    check_antialiasing("idle-analytic", Shot::new(AssistantState::Idle, 0.0), Antialiasing::Analytic);
+}
+
+#[test]
+fn thinking_msaa4() {
+    check_antialiasing("thinking-msaa4", Shot::new(AssistantState::Thinking, 0.7), Antialiasing::Msaa4);
+}
+
+//Кольца из шейдера расстояний должны выглядеть как сетка, но с ровными краями
+#[test]
+fn idle_sdf() {
+    check_sdf("idle-sdf", Shot::new(AssistantState::Idle, 0.0));
+}
+
+#[test]
+fn thinking_sdf() {
+    check_sdf("thinking-sdf", Shot::new(AssistantState::Thinking, 0.7));
+}
+
+#[test]
+fn listening_loud_sdf() {
+    check_sdf("listening-loud-sdf", Shot { level: 1.0, ..Shot::new(AssistantState::Listening, 1.5) });
+}
+
+//Свечение по настройкам состояния: по умолчанию и с более сильным свечением у думающего кольца
+#[test]
+fn idle_bloom() {
+    check_bloom("idle-bloom", Shot::new(AssistantState::Idle, 0.0), Config::default());
+}
+
+#[test]
+fn thinking_bloom() {
+    let mut config = Config::default();
+    config.bloom.thinking.intensity = 1.5;
+    config.bloom.thinking.radius = 2.0;
+    check_bloom("thinking-bloom", Shot::new(AssistantState::Thinking, 0.7), config);
+}
+
+#[test]
+fn thinking_bloom_msaa4() {
+    let mut config = Config::default();
+    config.render.antialiasing = Antialiasing::Msaa4;
+    check_bloom("thinking-bloom-msaa4", Shot::new(AssistantState::Thinking, 0.7), config);
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use render::config::{Config, RenderConfig};
+use render::ui::bloom::Bloom;
+use render::ui::colors::DEFAULT_THEME;
+use render::ui::headless::{self, Headless, HeadlessError, Image, Shot};
+use render::ui::lifecycle::AssistantState;
+use render::ui::renderer::{Ring, RingShape, Scene, SceneLayer};
+use render::ui::setup::{self, SetupError};
+
+fn headless(width: u32, height: u32) -> Option<Headless> {
+    match pollster::block_on(Headless::new(Config::default(), width, height, true)) {
+        Ok(headless) => Some(headless),
+        Err(HeadlessError::NoAdapter) => {
+            common::no_adapter("headless");
+            None
+        }
+        Err(err) => panic!("{err}"),
+    }
+}
+
+fn pixel(image: &Image, x: u32, y: u32) -> [u8; 4] {
+    let start = ((y * image.width + x) * 4) as usize;
+    image.pixels[start..start + 4].try_into().unwrap()
+}
+
+#[test]
+fn renders_ring_over_background() {
+    let Some(mut headless) = headless(200, 300) else { return };
+    let image = headless.render(&Shot::new(AssistantState::Idle, 0.0));
+
+    assert_eq!((image.width, image.height), (200, 300));
+    assert_eq!(image.pixels.len(), 200 * 300 * 4);
+    //Центр и углы - фон, где-то на кольце есть цвет
+    assert_eq!(pixel(&image, 100, 150), [0, 0, 0, 255]);
+    assert_eq!(pixel(&image, 0, 0), [0, 0, 0, 255]);
+    assert!(image.pixels.chunks(4).any(|p| p[2] > 128));
+}
+
+#[test]
+fn same_shot_renders_the_same_image() {
+    let Some(mut headless) = headless(120, 160) else { return };
+    let shot = Shot::new(AssistantState::Thinking, 0.7);
+    let first = headless.render(&shot);
+    headless.render(&Shot::new(AssistantState::Error, 0.2));
+    assert_eq!(headless.render(&shot), first);
+}
+
+#[test]
+fn png_round_trip() {
+    let Some(mut headless) = headless(64, 64) else { return };
+    let image = headless.render(&Shot::new(AssistantState::Speaking, 1.0));
+
+    let path = std::env::temp_dir().join(format!("jarvis-test-{}-shot.png", std::process::id()));
+    image.save_png(&path).unwrap();
+    let loaded = Image::load_png(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, image);
+}
+
+#[test]
+fn capture_draws_a_ready_scene() {
+    let (_, device, queue) = match pollster::block_on(setup::headless(true)) {
+        Ok(hardware) => hardware,
+        Err(SetupError::NoAdapter) => return common::no_adapter("headless setup"),
+        Err(err) => panic!("{err}"),
+    };
+    let layer = SceneLayer {
+        ring: Ring::Full,
+        outer_offsets: Vec::new(),
+        inner_offsets: Vec::new(),
+        scale: 0.8,
+        rotation: 0.0,
+        tint: [1.0; 4],
+    };
+    let scene = Scene { theme: DEFAULT_THEME, layers: vec![layer], overlay: Vec::new(), bloom: Bloom::default() };
+    let image = headless::capture(&device, &queue, &scene, RingShape::default(), RenderConfig::default(), (120, 90));
+
+    assert_eq!((image.width, image.height), (120, 90));
+    assert_eq!(pixel(&image, 60, 45), [0, 0, 0, 255]);
+    assert!(image.pixels.chunks(4).any(|p| p[2] > 128));
+}
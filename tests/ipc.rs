@@ -0,0 +1,155 @@
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+use render::ipc::client::Client;
+use render::ipc::protocol::{Command, Event};
+use render::ipc::server::Server;
+use render::ui::animator::Animator;
+use render::ui::clock::ManualClock;
+use render::ui::lifecycle::AssistantState;
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("jarvis-test-{}-{name}.sock", std::process::id()))
+}
+
+fn start(name: &str) -> (Server, mpsc::Receiver<Command>) {
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    let server = Server::start(&socket_path(name), move |command| {
+        sender.lock().unwrap().send(command).unwrap();
+    })
+    .unwrap();
+    (server, receiver)
+}
+
+#[test]
+fn commands_reach_handler() {
+    let (server, commands) = start("commands");
+    let mut client = Client::connect(server.path()).unwrap();
+
+    let sent = [
+        Command::SetState { state: AssistantState::Listening },
+        Command::SetLevel { level: 0.5 },
+        Command::ShowCaption { text: "Привет".to_string() },
+        Command::SetTheme { theme: "cyan".to_string() },
+        Command::Quit,
+    ];
+    for command in &sent {
+        client.send(command).unwrap();
+    }
+    for command in sent {
+        assert_eq!(commands.recv_timeout(Duration::from_secs(5)).unwrap(), command);
+    }
+}
+
+#[test]
+fn raw_json_lines_are_accepted() {
+    let (server, commands) = start("raw");
+    let mut stream = UnixStream::connect(server.path()).unwrap();
+    stream
+        .write_all(b"{\"cmd\":\"set-state\",\"state\":\"thinking\"}\n\n{\"cmd\":\"quit\"}\n")
+        .unwrap();
+
+    assert_eq!(
+        commands.recv_timeout(Duration::from_secs(5)).unwrap(),
+        Command::SetState { state: AssistantState::Thinking }
+    );
+    assert_eq!(commands.recv_timeout(Duration::from_secs(5)).unwrap(), Command::Quit);
+}
+
+#[test]
+fn events_are_broadcast_to_clients() {
+    let (server, commands) = start("events");
+    let mut first = Client::connect(server.path()).unwrap();
+    let mut second = Client::connect(server.path()).unwrap();
+
+    //Сервер регистрирует клиентов в своем потоке, ждем пока оба подключатся
+    first.send(&Command::SetLevel { level: 0.0 }).unwrap();
+    second.send(&Command::SetLevel { level: 0.0 }).unwrap();
+    commands.recv_timeout(Duration::from_secs(5)).unwrap();
+    commands.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    server.emit(&Event::RecordToggled { recording: true });
+    server.emit(&Event::WindowClosed);
+
+    for client in [&mut first, &mut second] {
+        assert_eq!(client.next_event().unwrap(), Some(Event::RecordToggled { recording: true }));
+        assert_eq!(client.next_event().unwrap(), Some(Event::WindowClosed));
+    }
+}
+
+#[test]
+fn malformed_command_reports_error() {
+    let (server, _commands) = start("malformed");
+    let mut stream = UnixStream::connect(server.path()).unwrap();
+    stream.write_all(b"{\"cmd\":\"dance\"}\n").unwrap();
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).unwrap();
+
+    let event: Event = serde_json::from_str(&line).unwrap();
+    assert!(matches!(event, Event::Error { .. }));
+}
+
+#[test]
+fn rejected_state_change_reports_error() {
+    let (server, commands) = start("rejected");
+    let mut client = Client::connect(server.path()).unwrap();
+    client.send(&Command::SetState { state: AssistantState::Thinking }).unwrap();
+
+    //Как в цикле событий окна: из Muted можно только в Idle
+    let mut animator = Animator::new(Box::new(ManualClock::new()), AssistantState::Muted);
+    let Command::SetState { state: next } = commands.recv_timeout(Duration::from_secs(5)).unwrap() else {
+        panic!("expected set-state");
+    };
+    let previous = animator.state();
+    if !animator.transition(next) {
+        server.emit(&Event::rejected_state(previous, next));
+    }
+
+    assert_eq!(
+        client.next_event().unwrap(),
+        Some(Event::Error { message: "can't go from Muted to Thinking".to_string() })
+    );
+    assert_eq!(animator.state(), AssistantState::Muted);
+}
+
+#[test]
+fn socket_file_is_removed_on_drop() {
+    let (server, _commands) = start("drop");
+    let path = server.path().to_path_buf();
+    assert!(path.exists());
+
+    drop(server);
+    assert!(!path.exists());
+}
+
+#[test]
+fn client_that_never_reads_does_not_block_emit() {
+    let (server, commands) = start("stalled");
+    let stalled = UnixStream::connect(server.path()).unwrap();
+    let mut reader = Client::connect(server.path()).unwrap();
+    (&stalled).write_all(b"{\"cmd\":\"set-level\",\"level\":0.0}\n").unwrap();
+    reader.send(&Command::SetLevel { level: 0.0 }).unwrap();
+    commands.recv_timeout(Duration::from_secs(5)).unwrap();
+    commands.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    //Намного больше буфера сокета: с блокирующей записью emit здесь бы встал
+    let message = "x".repeat(64 * 1024);
+    let started = std::time::Instant::now();
+    for _ in 0..100 {
+        server.emit(&Event::Error { message: message.clone() });
+    }
+    server.emit(&Event::WindowClosed);
+    assert!(started.elapsed() < Duration::from_secs(1), "emit took {:?}", started.elapsed());
+
+    //Читающий клиент получает все, застрявшего сервер отключает
+    for _ in 0..100 {
+        assert!(matches!(reader.next_event().unwrap(), Some(Event::Error { .. })));
+    }
+    assert_eq!(reader.next_event().unwrap(), Some(Event::WindowClosed));
+    drop(stalled);
+}
@@ -0,0 +1,138 @@
+use render::ui::camera::{Camera, Units};
+use render::ui::debug::{self, FrameGraph};
+use render::ui::hit_test::hit_ring;
+use render::ui::menu::{Menu, MenuItem};
+use render::ui::overlay::{Quads, MAX_VERTICES};
+use render::ui::renderer::{Ring, RingShape, SceneLayer};
+
+const SIZE: (u32, u32) = (400, 400);
+const CENTER: (f64, f64) = (200.0, 200.0);
+
+fn layer(ring: Ring, scale: f32, rotation: f32) -> SceneLayer {
+    SceneLayer {
+        ring,
+        outer_offsets: Vec::new(),
+        inner_offsets: Vec::new(),
+        scale,
+        rotation,
+        tint: [1.0; 4],
+    }
+}
+
+//Точка на расстоянии distance пикселей от центра под углом degrees (против часовой, как на экране)
+fn at(distance: f64, degrees: f64) -> (f64, f64) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    (CENTER.0 + distance * cos, CENTER.1 - distance * sin)
+}
+
+#[test]
+fn ring_is_hit_between_inner_and_outer_radius() {
+    let shape = RingShape::default();
+    let ring = layer(Ring::Full, 0.8, 0.0);
+    assert!(!hit_ring(&ring, &shape, CENTER, SIZE));
+    assert!(hit_ring(&ring, &shape, at(60.0, 0.0), SIZE));
+    assert!(hit_ring(&ring, &shape, at(60.0, 135.0), SIZE));
+    assert!(!hit_ring(&ring, &shape, at(100.0, 0.0), SIZE));
+}
+
+#[test]
+fn hit_follows_scale() {
+    let shape = RingShape::default();
+    let ring = layer(Ring::Full, 1.6, 0.0);
+    assert!(!hit_ring(&ring, &shape, at(60.0, 0.0), SIZE));
+    assert!(hit_ring(&ring, &shape, at(120.0, 0.0), SIZE));
+}
+
+#[test]
+fn ring_stays_round_in_tall_windows() {
+    let shape = RingShape::default();
+    let ring = layer(Ring::Full, 0.8, 0.0);
+    let size = (400, 1080);
+    for position in [(260.0, 540.0), (200.0, 480.0), (140.0, 540.0), (200.0, 600.0)] {
+        assert!(hit_ring(&ring, &shape, position, size));
+    }
+    assert!(!hit_ring(&ring, &shape, (200.0, 440.0), size));
+}
+
+#[test]
+fn pixel_camera_uses_logical_pixels() {
+    let camera = Camera::new((400, 1080), 2.0, Units::Pixels);
+    assert_eq!(camera.unproject((400.0, 1080.0)), [200.0, 540.0]);
+    let center = Camera::new((400, 1080), 1.0, Units::Normalized).unproject((200.0, 540.0));
+    assert_eq!(center, [0.0, 0.0]);
+}
+
+#[test]
+fn dash_gaps_follow_rotation() {
+    let shape = RingShape::default();
+    //Штрих [15, 15] по 2 градуса: 0-29 градусов нарисованы, 30-59 - пропуск
+    assert!(hit_ring(&layer(Ring::Dashed, 0.8, 0.0), &shape, at(60.0, 10.0), SIZE));
+    assert!(!hit_ring(&layer(Ring::Dashed, 0.8, 0.0), &shape, at(60.0, 45.0), SIZE));
+    assert!(hit_ring(&layer(Ring::Dashed, 0.8, 40f32.to_radians()), &shape, at(60.0, 45.0), SIZE));
+}
+
+#[test]
+fn audio_offsets_widen_the_ring() {
+    let shape = RingShape::default();
+    let mut ring = layer(Ring::Full, 0.8, 0.0);
+    assert!(!hit_ring(&ring, &shape, at(85.0, 0.0), SIZE));
+    ring.outer_offsets = vec![0.15; 180];
+    assert!(hit_ring(&ring, &shape, at(85.0, 0.0), SIZE));
+}
+
+#[test]
+fn menu_items_stack_from_the_click() {
+    let menu = Menu::open((10.0, 10.0), SIZE, false);
+    assert_eq!(menu.item_at((20.0, 20.0)), Some(MenuItem::Mute));
+    assert_eq!(menu.item_at((20.0, 45.0)), Some(MenuItem::Theme));
+    assert_eq!(menu.item_at((20.0, 80.0)), Some(MenuItem::Quit));
+    assert_eq!(menu.item_at((20.0, 100.0)), None);
+    assert_eq!(menu.item_at((5.0, 20.0)), None);
+}
+
+#[test]
+fn menu_stays_inside_the_window() {
+    let menu = Menu::open((390.0, 390.0), SIZE, true);
+    assert_eq!(menu.item_at((390.0, 390.0)), Some(MenuItem::Quit));
+    assert_eq!(menu.item_at((285.0, 320.0)), Some(MenuItem::Mute));
+}
+
+#[test]
+fn menu_fits_the_overlay_buffer() {
+    let muted = Menu::open((0.0, 0.0), SIZE, true).vertices([1.0; 4], 1.0);
+    let unmuted = Menu::open((0.0, 0.0), SIZE, false).vertices([1.0; 4], 1.0);
+    //UNMUTE длиннее MUTE
+    assert!(muted.len() > unmuted.len());
+    assert!(muted.len() <= MAX_VERTICES);
+    assert_eq!(muted.len() % 3, 0);
+}
+
+#[test]
+fn frame_graph_keeps_the_latest_frames() {
+    let mut graph = FrameGraph::default();
+    assert_eq!(graph.vertices(1.0).len(), 12);
+    for frame in 0..debug::FRAMES + 5 {
+        graph.push(frame as f32);
+    }
+    let times: Vec<f32> = graph.times().collect();
+    assert_eq!(times.len(), debug::FRAMES);
+    assert_eq!(times[0], 5.0);
+    assert_eq!(*times.last().unwrap(), (debug::FRAMES + 4) as f32);
+    assert!(graph.vertices(1.0).len() + 3200 <= MAX_VERTICES);
+}
+
+#[test]
+fn overlay_lines_snap_to_physical_pixels() {
+    let mut quads = Quads::new(1.5);
+    quads.rect(10.1, 20.0, 0.2, 5.0, [1.0; 4]);
+    let vertices = quads.into_vertices();
+    let floats: &[f32] = bytemuck::cast_slice(&vertices);
+    let xs: Vec<f32> = floats.chunks(7).map(|vertex| vertex[0] * 1.5).collect();
+    let ys: Vec<f32> = floats.chunks(7).map(|vertex| vertex[1] * 1.5).collect();
+    for value in xs.iter().chain(&ys) {
+        assert!((value - value.round()).abs() < 1e-4, "{value} is not on the pixel grid");
+    }
+    //Линия тоньше пикселя все равно занимает один физический пиксель
+    let width = xs.iter().cloned().fold(f32::MIN, f32::max) - xs.iter().cloned().fold(f32::MAX, f32::min);
+    assert!((width - 1.0).abs() < 1e-4);
+}
@@ -0,0 +1,46 @@
+use render::ui::surface::{recovery, Recovery, Visibility};
+use wgpu::SurfaceError;
+
+#[test]
+fn surface_errors_map_to_recovery() {
+    assert_eq!(recovery(&SurfaceError::Lost), Recovery::Reconfigure);
+    assert_eq!(recovery(&SurfaceError::Outdated), Recovery::Reconfigure);
+    assert_eq!(recovery(&SurfaceError::Timeout), Recovery::SkipFrame);
+    assert_eq!(recovery(&SurfaceError::OutOfMemory), Recovery::Exit);
+}
+
+#[test]
+fn minimizing_pauses_until_restored() {
+    let mut visibility = Visibility::new(400, 1080);
+    assert!(!visibility.is_paused());
+
+    //0x0 не настраиваем
+    assert!(!visibility.resize(0, 0));
+    assert!(visibility.is_paused());
+
+    assert!(visibility.resize(400, 1080));
+    assert!(!visibility.is_paused());
+}
+
+#[test]
+fn zero_width_alone_also_pauses() {
+    let mut visibility = Visibility::new(400, 1080);
+    assert!(!visibility.resize(0, 1080));
+    assert!(visibility.is_paused());
+    assert!(!visibility.resize(400, 0));
+    assert!(visibility.is_paused());
+}
+
+#[test]
+fn occluded_window_pauses_without_touching_the_size() {
+    let mut visibility = Visibility::new(400, 1080);
+    visibility.set_occluded(true);
+    assert!(visibility.is_paused());
+
+    //Пока окно закрыто, размер может поменяться - поверхность все равно настраиваем
+    assert!(visibility.resize(300, 600));
+    assert!(visibility.is_paused());
+
+    visibility.set_occluded(false);
+    assert!(!visibility.is_paused());
+}
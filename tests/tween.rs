@@ -0,0 +1,95 @@
+use render::ui::tween::{Animation, Easing, Pose, Repeat, Track};
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-4, "{a} != {b}");
+}
+
+#[test]
+fn easings_start_at_zero_and_end_at_one() {
+    let easings = [
+        Easing::Linear,
+        Easing::EaseInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::Elastic,
+        Easing::Spring { damping: 6.0, frequency: 2.0 },
+    ];
+    for easing in easings {
+        assert_close(easing.apply(0.0), 0.0);
+        assert_close(easing.apply(1.0), 1.0);
+    }
+    assert_close(Easing::EaseInOut.apply(0.5), 0.5);
+    assert_close(Easing::CubicInOut.apply(0.5), 0.5);
+    assert!(Easing::CubicIn.apply(0.5) < 0.5);
+    assert!(Easing::CubicOut.apply(0.5) > 0.5);
+}
+
+#[test]
+fn elastic_and_spring_overshoot() {
+    let overshoots = |easing: Easing| (1..100).any(|i| easing.apply(i as f32 / 100.0) > 1.0);
+    assert!(overshoots(Easing::Elastic));
+    assert!(overshoots(Easing::Spring { damping: 4.0, frequency: 2.0 }));
+}
+
+#[test]
+fn track_interpolates_between_keys() {
+    let track = Track::from(0.0).key(1.0, 10.0, Easing::Linear).key(3.0, 0.0, Easing::Linear);
+    assert_close(track.sample(-1.0), 0.0);
+    assert_close(track.sample(0.5), 5.0);
+    assert_close(track.sample(2.0), 5.0);
+    assert_close(track.sample(10.0), 0.0);
+    assert_close(track.duration(), 3.0);
+}
+
+#[test]
+fn track_repeat_modes() {
+    let track = Track::from(0.0).key(2.0, 1.0, Easing::Linear);
+
+    let looped = track.clone().repeat(Repeat::Loop);
+    assert_close(looped.sample(2.5), 0.25);
+    assert_close(looped.sample(5.0), 0.5);
+
+    let ping_pong = track.repeat(Repeat::PingPong);
+    assert_close(ping_pong.sample(1.0), 0.5);
+    assert_close(ping_pong.sample(3.0), 0.5);
+    assert_close(ping_pong.sample(3.5), 0.25);
+    assert_close(ping_pong.sample(4.0), 0.0);
+}
+
+#[test]
+fn color_tracks_interpolate_per_channel() {
+    let track = Track::from([0.0, 0.0, 0.0, 1.0]).key(1.0, [1.0, 0.5, 0.0, 0.0], Easing::Linear);
+    assert_eq!(track.sample(0.5), [0.5, 0.25, 0.0, 0.5]);
+}
+
+#[test]
+fn constant_track_ignores_time() {
+    let track = Track::constant(0.6).repeat(Repeat::Loop);
+    assert_close(track.sample(0.0), 0.6);
+    assert_close(track.sample(42.0), 0.6);
+}
+
+#[test]
+fn composed_animations_multiply_and_add() {
+    let spin = Animation::new()
+        .rotation(Track::from(0.0).key(1.0, 1.0, Easing::Linear))
+        .scale(Track::constant(2.0));
+    let pulse = Animation::new()
+        .rotation(Track::constant(0.5))
+        .scale(Track::constant(1.5))
+        .radius(Track::constant(0.1))
+        .opacity(Track::constant(0.5));
+
+    let pose = spin.compose(pulse).sample(0.5);
+    assert_close(pose.rotation, 1.0);
+    assert_close(pose.scale, 3.0);
+    assert_close(pose.radius, 0.1);
+    assert_close(pose.opacity, 0.5);
+    assert_eq!(pose.color, Pose::default().color);
+}
+
+#[test]
+fn empty_animation_is_identity() {
+    assert_eq!(Animation::new().sample(3.0), Pose::default());
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use wgpu::CompositeAlphaMode::{Auto, Inherit, Opaque, PostMultiplied, PreMultiplied};
+
+use render::ui::placement::Placement;
+use render::config::{Antialiasing, PresentMode};
+use render::ui::colors;
+use render::ui::setup::{self, alpha_mode, fallback_antialiasing, present_mode, surface_format, SetupError};
+
+#[test]
+fn transparent_surface_prefers_premultiplied_alpha() {
+    assert_eq!(alpha_mode(&[Opaque, PostMultiplied, PreMultiplied], true), PreMultiplied);
+    assert_eq!(alpha_mode(&[Opaque, Inherit], true), Inherit);
+    assert_eq!(alpha_mode(&[Opaque, PostMultiplied], true), PostMultiplied);
+    //Прозрачность недоступна - остается непрозрачное окно
+    assert_eq!(alpha_mode(&[Opaque], true), Opaque);
+}
+
+#[test]
+fn opaque_surface_ignores_alpha() {
+    assert_eq!(alpha_mode(&[PreMultiplied, Opaque], false), Opaque);
+    assert_eq!(alpha_mode(&[Auto], false), Auto);
+}
+
+#[test]
+fn placement_survives_restart() {
+    let path = std::env::temp_dir()
+        .join(format!("jarvis-test-{}-state", std::process::id()))
+        .join("window.toml");
+    assert_eq!(Placement::load(&path), None);
+
+    let placement = Placement { x: -120, y: 48 };
+    placement.save(&path).unwrap();
+    assert_eq!(Placement::load(&path), Some(placement));
+
+    std::fs::write(&path, "x = \"left\"").unwrap();
+    assert_eq!(Placement::load(&path), None);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn setup_errors_name_the_failed_stage() {
+    let none = SetupError::NoAdapter.to_string();
+    assert!(none.contains("no graphics adapter"), "{none}");
+    assert!(none.contains("software"), "{none}");
+
+    let surface = SetupError::UnsupportedSurface { adapter: "llvmpipe".to_string() }.to_string();
+    assert!(surface.contains("`llvmpipe` can't present to this window"), "{surface}");
+}
+
+#[test]
+fn headless_setup_falls_back_to_some_adapter() {
+    //Программный адаптер есть почти везде; если нет - ошибка должна быть NoAdapter, а не паника
+    match pollster::block_on(setup::headless(false)) {
+        Ok(_) => {}
+        Err(SetupError::NoAdapter) => common::no_adapter("headless setup"),
+        Err(err) => panic!("{err}"),
+    }
+}
+
+#[test]
+fn surface_prefers_srgb_formats() {
+    use wgpu::TextureFormat::*;
+
+    assert_eq!(surface_format(&[Bgra8Unorm, Bgra8UnormSrgb]), Bgra8UnormSrgb);
+    assert_eq!(surface_format(&[Rgba8Unorm, Rgba8UnormSrgb]), Rgba8UnormSrgb);
+    assert_eq!(surface_format(&[Rgba16Float, Rgb10a2Unorm]), Rgba16Float);
+}
+
+#[test]
+fn present_mode_falls_back_to_the_closest() {
+    use wgpu::PresentMode::*;
+
+    assert_eq!(present_mode(&[Fifo, Mailbox, Immediate], PresentMode::Immediate), Immediate);
+    assert_eq!(present_mode(&[Fifo, Mailbox], PresentMode::Immediate), Mailbox);
+    assert_eq!(present_mode(&[Fifo, Immediate], PresentMode::Mailbox), Fifo);
+    assert_eq!(present_mode(&[Immediate, Fifo], PresentMode::Vsync), Fifo);
+}
+
+#[test]
+fn antialiasing_falls_back_to_supported_sample_counts() {
+    let up_to_4x = |count: u32| count <= 4;
+    assert_eq!(fallback_antialiasing(Antialiasing::Msaa8, |_| true), Antialiasing::Msaa8);
+    assert_eq!(fallback_antialiasing(Antialiasing::Msaa8, up_to_4x), Antialiasing::Msaa4);
+    assert_eq!(fallback_antialiasing(Antialiasing::Msaa4, up_to_4x), Antialiasing::Msaa4);
+    //Мультисэмплинга нет вовсе - сглаживаем постобработкой
+    assert_eq!(fallback_antialiasing(Antialiasing::Msaa8, |count| count == 1), Antialiasing::Smaa);
+    assert_eq!(fallback_antialiasing(Antialiasing::Analytic, |count| count == 1), Antialiasing::Analytic);
+}
+
+#[test]
+fn srgb_conversion_round_trips() {
+    assert_eq!(colors::srgb_to_linear(0.0), 0.0);
+    assert_eq!(colors::srgb_to_linear(1.0), 1.0);
+    //Середина sRGB - примерно пятая часть линейной яркости
+    assert!((colors::srgb_to_linear(0.5) - 0.214).abs() < 0.001);
+    for step in 0..=255 {
+        let value = step as f32 / 255.0;
+        assert!((colors::linear_to_srgb(colors::srgb_to_linear(value)) - value).abs() < 1e-5);
+    }
+}